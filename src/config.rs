@@ -0,0 +1,181 @@
+use std::{collections::HashMap, env, net::SocketAddr, path::PathBuf, time::Duration};
+
+use teloxide::types::ChatId;
+
+use crate::domain::{
+    fetch::{HttpConfig, OpenMensaConfig, PrefetchSchedule},
+    model::{parse::parse_canteen, Canteen},
+};
+
+const DEFAULT_SETTINGS_PATH: &str = "fressbot-settings.json";
+
+/// Runtime configuration of the bot, read from environment variables.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// File the user settings are persisted to (`SETTINGS_FILE`).
+    pub settings_path: PathBuf,
+    /// Chat that is alerted when the menu pages change their layout (`ADMIN_CHAT_ID`).
+    pub admin_chat: Option<ChatId>,
+    /// Directory the menu cache is persisted to, if any (`MENU_CACHE_DIR`).
+    pub menu_cache_dir: Option<PathBuf>,
+    /// How long stale menus may still be served while they are refreshed, `None` for the
+    /// default (`MENU_MAX_STALE` in minutes).
+    pub menu_max_stale: Option<Duration>,
+    /// When the menus are prefetched on weekdays, `None` if disabled (`PREFETCH_SCHEDULE`, e.g.
+    /// "06:30-14:30/30" or "off").
+    pub prefetch: Option<PrefetchSchedule>,
+    /// HTTP client settings (`MENU_BASE_URL`, `HTTP_USER_AGENT` or `HTTP_CONTACT`,
+    /// `HTTP_CONNECT_TIMEOUT` and `HTTP_READ_TIMEOUT` in seconds, `HTTP_PROXY_URL`,
+    /// `HTTP_RATE_LIMIT` in requests per second, `HTTP_MAX_CONNECTIONS`).
+    pub http: HttpConfig,
+    /// Whether OpenMensa is the primary or fallback source of menus (`OPENMENSA`, "off",
+    /// "primary" or "fallback"), its URL (`OPENMENSA_BASE_URL`) and IDs of canteens
+    /// (`OPENMENSA_CANTEEN_IDS`, e.g. "academica=187,vita=94").
+    pub openmensa: OpenMensaConfig,
+    /// Address the JSON API and OpenMensa feeds are served on, if any (`SERVER_ADDR`, e.g.
+    /// "0.0.0.0:8080"). Needs the `server` feature.
+    pub server_addr: Option<SocketAddr>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let settings_path = env::var_os("SETTINGS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| DEFAULT_SETTINGS_PATH.into());
+
+        let admin_chat = env::var("ADMIN_CHAT_ID").ok().and_then(|id| {
+            id.trim()
+                .parse()
+                .inspect_err(|e| log::warn!("ADMIN_CHAT_ID \"{id}\" is not a chat id - {e}"))
+                .ok()
+                .map(ChatId)
+        });
+
+        let menu_cache_dir = env::var_os("MENU_CACHE_DIR").map(PathBuf::from);
+        let menu_max_stale =
+            parsed("MENU_MAX_STALE").map(|mins: u64| Duration::from_secs(mins.saturating_mul(60)));
+
+        let prefetch = match env::var("PREFETCH_SCHEDULE") {
+            Ok(s) if s.trim().eq_ignore_ascii_case("off") => None,
+            Ok(s) => s
+                .parse()
+                .inspect_err(|e| log::warn!("PREFETCH_SCHEDULE \"{s}\" is invalid - {e}"))
+                .ok()
+                .or_else(|| Some(PrefetchSchedule::default())),
+            Err(_) => Some(PrefetchSchedule::default()),
+        };
+
+        Self {
+            settings_path,
+            admin_chat,
+            menu_cache_dir,
+            menu_max_stale,
+            prefetch,
+            http: http_config_from_env(),
+            openmensa: openmensa_config_from_env(),
+            server_addr: parsed("SERVER_ADDR"),
+        }
+    }
+}
+
+/// Parses the environment variable `name`, warning about values that do not parse.
+fn parsed<T>(name: &str) -> Option<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = env::var(name).ok()?;
+    value
+        .trim()
+        .parse()
+        .inspect_err(|e| log::warn!("{name} \"{value}\" is invalid - {e}"))
+        .ok()
+}
+
+fn http_config_from_env() -> HttpConfig {
+    let defaults = HttpConfig::default();
+
+    let secs = |name: &str| parsed(name).map(Duration::from_secs);
+
+    let user_agent = env::var("HTTP_USER_AGENT").unwrap_or_else(|_| {
+        HttpConfig::user_agent_with_contact(env::var("HTTP_CONTACT").ok().as_deref())
+    });
+
+    HttpConfig {
+        base_url: env::var("MENU_BASE_URL").unwrap_or(defaults.base_url),
+        user_agent,
+        connect_timeout: secs("HTTP_CONNECT_TIMEOUT").unwrap_or(defaults.connect_timeout),
+        read_timeout: secs("HTTP_READ_TIMEOUT").unwrap_or(defaults.read_timeout),
+        proxy: env::var("HTTP_PROXY_URL").ok(),
+        requests_per_sec: parsed("HTTP_RATE_LIMIT")
+            .and_then(positive_rate)
+            .unwrap_or(defaults.requests_per_sec),
+        max_connections: parsed("HTTP_MAX_CONNECTIONS").unwrap_or(defaults.max_connections),
+    }
+}
+
+/// Keeps a rate limit only if it is positive and finite, warning about others.
+fn positive_rate(rate: f64) -> Option<f64> {
+    if rate.is_finite() && rate > 0.0 {
+        Some(rate)
+    } else {
+        log::warn!("HTTP_RATE_LIMIT {rate} is not a positive number of requests per second");
+        None
+    }
+}
+
+fn openmensa_config_from_env() -> OpenMensaConfig {
+    let canteen_ids = env::var("OPENMENSA_CANTEEN_IDS")
+        .map(|ids| parse_canteen_ids(&ids))
+        .unwrap_or_default();
+
+    OpenMensaConfig {
+        usage: parsed("OPENMENSA").unwrap_or_default(),
+        base_url: env::var("OPENMENSA_BASE_URL").ok(),
+        canteen_ids,
+    }
+}
+
+/// Parses a list of canteens and their OpenMensa IDs, e.g. "academica=187,vita=94".
+///
+/// Entries that do not parse are skipped with a warning.
+fn parse_canteen_ids(ids: &str) -> HashMap<Canteen, u32> {
+    ids.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(canteen, id)| {
+                let (_, canteen) = parse_canteen(canteen.trim()).ok()?;
+                Some((canteen, id.trim().parse().ok()?))
+            });
+            if parsed.is_none() {
+                log::warn!("OPENMENSA_CANTEEN_IDS entry \"{entry}\" is invalid");
+            }
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::domain::model::Canteen;
+
+    use super::{parse_canteen_ids, positive_rate};
+
+    #[test]
+    fn it_parses_openmensa_canteen_ids() {
+        assert_eq!(
+            parse_canteen_ids("academica=187, Mensa Vita = 94,kmac,süd=x"),
+            HashMap::from([(Canteen::Academica, 187), (Canteen::Vita, 94)])
+        );
+    }
+
+    #[test]
+    fn it_rejects_rate_limits_that_are_not_positive() {
+        assert_eq!(positive_rate(0.5), Some(0.5));
+        for rate in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(positive_rate(rate), None);
+        }
+    }
+}
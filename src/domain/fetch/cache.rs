@@ -1,126 +1,675 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
 use lru::LruCache;
+use strum::{EnumCount, IntoEnumIterator};
 
-use crate::domain::model::{Canteen, Menu};
+use crate::domain::model::{Canteen, DayMenu, Menu, WeekMenu};
 use std::sync::{Arc, Mutex};
 
-use super::HtmlMenuFetcher;
+use super::{
+    err::FetcherError, menu_of_day, FetchMetricsSnapshot, MenuProvider, MenuStore, MenuWeek,
+    Revalidated, SingleFlight, StoredWeekMenu, Validators,
+};
 
-const DEFAULT_CACHE_SIZE: usize = 16;
+/// Room for both week pages of every canteen.
+const DEFAULT_CACHE_SIZE: usize = Canteen::COUNT * MenuWeek::COUNT;
 
+/// Week menus keyed by the Monday of their week. A [`MenuWeek`] would point at another week after
+/// Sunday.
+type WeekMenuCache = LruCache<(Canteen, NaiveDate), CacheEntry<WeekMenu>>;
+
+/// Caches the week menus of another provider.
 #[derive(Debug, Clone)]
-pub struct HtmlMenuFetcherWithCache {
-    cache: Arc<Mutex<LruCache<(Canteen, chrono::NaiveDate), CacheEntry<Menu>>>>,
-    fetcher: HtmlMenuFetcher,
+pub struct MenuCache {
+    cache: Arc<Mutex<WeekMenuCache>>,
+    inner: Arc<dyn MenuProvider>,
     cache_fresh_dur: std::time::Duration,
+    /// How long a stale entry may still be served while it is refreshed in the background
+    max_stale_dur: std::time::Duration,
+    in_flight: SingleFlight<(Canteen, NaiveDate), Result<WeekMenu, Arc<anyhow::Error>>>,
+    store: Option<Arc<dyn MenuStore>>,
 }
 
-impl HtmlMenuFetcherWithCache {
-    pub fn new() -> Self {
+impl MenuCache {
+    pub fn new(inner: Arc<dyn MenuProvider>) -> Self {
         let cache = LruCache::new(DEFAULT_CACHE_SIZE.try_into().unwrap());
 
         Self {
             cache: Arc::new(Mutex::new(cache)),
-            fetcher: HtmlMenuFetcher::new(),
+            inner,
             cache_fresh_dur: std::time::Duration::from_secs(10 * 60),
+            max_stale_dur: std::time::Duration::from_secs(6 * 60 * 60),
+            in_flight: SingleFlight::new(),
+            store: None,
+        }
+    }
+
+    /// Persists the cache in `store` and fills it with the menus stored there.
+    ///
+    /// Stored menus keep the time they were fetched at. Menus of past weeks are left out.
+    pub fn with_store(self, store: Arc<dyn MenuStore>) -> Self {
+        let this_monday = MenuWeek::Current.monday(Local::now().date_naive());
+
+        match store.load_all() {
+            Ok(menus) => {
+                for stored in menus {
+                    let monday = stored.monday();
+                    if monday < this_monday {
+                        continue;
+                    }
+
+                    log::info!(
+                        "Loaded the menu of ({}, {:?}) fetched at {}",
+                        stored.canteen,
+                        stored.week,
+                        stored.menu.fetched_at()
+                    );
+                    self.put(stored.canteen, monday, stored.menu, stored.validators);
+                }
+            }
+            Err(e) => log::warn!("Can not load the stored menus: {e}"),
         }
+
+        Self {
+            store: Some(store),
+            ..self
+        }
+    }
+
+    pub fn with_fresh_dur(mut self, fresh_dur: std::time::Duration) -> Self {
+        self.cache_fresh_dur = fresh_dur;
+        self
     }
 
-    pub async fn fetch_daily_menu(
+    pub fn with_max_stale_dur(mut self, max_stale_dur: std::time::Duration) -> Self {
+        self.max_stale_dur = max_stale_dur;
+        self
+    }
+
+    /// Returns the week menu of `canteen` for `week` as seen from `today`.
+    async fn fetch_weekly_menu_on(
         &self,
-        day: chrono::NaiveDate,
         canteen: Canteen,
-    ) -> anyhow::Result<Menu> {
+        week: MenuWeek,
+        today: NaiveDate,
+    ) -> anyhow::Result<WeekMenu> {
+        let monday = week.monday(today);
         let cached_result = self
             .cache
             .lock()
-            .map_err(|e| {
-                log::warn!("Can not access cache: {}", e.to_string());
-                e
-            })
+            .inspect_err(|e| log::warn!("Can not access cache: {e}"))
             .ok()
             .and_then(|mut cache| {
-                let cache_entry = cache.get(&(canteen, day))?;
+                let cache_entry = cache.get(&(canteen, monday))?;
 
-                log::info!("Result for ({}, {}) is cached", &canteen, &day);
+                log::info!("Result for ({}, {:?}) is cached", &canteen, week);
 
                 if cache_entry.is_stale() {
-                    let expired_at = cache_entry.created + cache_entry.fresh_dur;
                     log::info!(
-                        "Cache entry for ({}, {}) is stale. Expired at {:?} ({} s ago)",
+                        "Cache entry for ({}, {:?}) is stale. Expired {} s ago",
                         &canteen,
-                        &day,
-                        expired_at,
-                        expired_at.elapsed().as_secs()
+                        week,
+                        cache_entry.staleness().as_secs()
                     );
-                    None
-                } else {
-                    Some(cache_entry.get_val().clone())
                 }
+
+                Some(cache_entry.clone())
             });
 
         match cached_result {
-            Some(menu) => Ok(menu),
-            None => self.fetch_and_insert(day, canteen).await,
+            Some(entry) if entry.is_fresh() => return Ok(entry.val),
+            Some(entry) if entry.staleness() <= self.max_stale_dur => {
+                let week_menu = entry.val.clone().into_stale();
+                self.refresh_in_background(canteen, week, monday, entry);
+
+                return Ok(week_menu);
+            }
+            _ => {}
+        }
+
+        self.refresh(canteen, week, monday, cached_result).await
+    }
+
+    /// Fails with [`FetcherError::LayoutChanged`] if no canteen lists a single dish on the weekday
+    /// `day`. An open canteen without dishes, or a canteen closed for lack of a menu on its page, is
+    /// a hint that the menus are no longer found.
+    ///
+    /// Only looks at cached menus, so it never waits for upstream. Canteens that are not cached
+    /// are unknown and the check passes.
+    fn check_weekday_has_dishes(&self, day: NaiveDate, canteen: Canteen) -> anyhow::Result<()> {
+        let Ok(cache) = self.cache.lock() else {
+            return Ok(());
+        };
+
+        let monday = MenuWeek::Current.monday(day);
+        for other in Canteen::iter() {
+            let Some(entry) = cache.peek(&(other, monday)) else {
+                // Unknown, so it does not count as a canteen without dishes
+                return Ok(());
+            };
+
+            if let Some(DayMenu::Open(menu)) = entry.val.day(day) {
+                if menu.dishes().next().is_some() {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(FetcherError::LayoutChanged {
+            canteen,
+            reason: format!("no canteen lists a dish on {}", day.format("%Y-%m-%d")),
         }
+        .into())
+    }
+
+    /// Serves the stale `entry` no longer than necessary by refreshing it in a background task.
+    fn refresh_in_background(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        monday: NaiveDate,
+        entry: CacheEntry<WeekMenu>,
+    ) {
+        let this = self.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = this.refresh(canteen, week, monday, Some(entry)).await {
+                log::warn!("Can not refresh the stale menu of ({canteen}, {week:?}): {e}");
+            }
+        });
+    }
+
+    /// Fetches the week menu from upstream, or revalidates `cached` if it has validators.
+    /// Concurrent refreshes of the same menu share one request.
+    async fn refresh(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        monday: NaiveDate,
+        cached: Option<CacheEntry<WeekMenu>>,
+    ) -> anyhow::Result<WeekMenu> {
+        self.in_flight
+            .run((canteen, monday), || async {
+                match cached {
+                    Some(entry) if !entry.validators.is_empty() => {
+                        self.revalidate_and_insert(canteen, week, monday, entry)
+                            .await
+                    }
+                    _ => self.fetch_and_insert(canteen, week, monday).await,
+                }
+                .map_err(Arc::new)
+            })
+            .await
+            .map_err(unshare_error)
     }
 
     async fn fetch_and_insert(
         &self,
-        day: chrono::NaiveDate,
         canteen: Canteen,
-    ) -> anyhow::Result<Menu> {
-        let menu = self.fetcher.fetch_daily_menu(day, canteen).await?;
+        week: MenuWeek,
+        monday: NaiveDate,
+    ) -> anyhow::Result<WeekMenu> {
+        match self
+            .inner
+            .fetch_weekly_menu_if_modified(canteen, week, &Validators::default())
+            .await?
+        {
+            Revalidated::Modified { val, validators } => {
+                Ok(self.insert(canteen, week, monday, val, validators))
+            }
+            // Without validators the page can not be unmodified
+            Revalidated::NotModified => Err(FetcherError::HttpStatus(304).into()),
+        }
+    }
+
+    /// Asks upstream whether the stale `entry` is still up to date. An unmodified page is not
+    /// parsed again, its entry just becomes fresh again.
+    async fn revalidate_and_insert(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        monday: NaiveDate,
+        entry: CacheEntry<WeekMenu>,
+    ) -> anyhow::Result<WeekMenu> {
+        match self
+            .inner
+            .fetch_weekly_menu_if_modified(canteen, week, &entry.validators)
+            .await?
+        {
+            Revalidated::Modified { val, validators } => {
+                Ok(self.insert(canteen, week, monday, val, validators))
+            }
+            Revalidated::NotModified => {
+                let week_menu = entry.val.with_fetched_at(Utc::now());
+                Ok(self.insert(canteen, week, monday, week_menu, entry.validators))
+            }
+        }
+    }
+
+    fn insert(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        monday: NaiveDate,
+        week_menu: WeekMenu,
+        validators: Validators,
+    ) -> WeekMenu {
+        if let Some(store) = self.store.clone() {
+            let stored = StoredWeekMenu {
+                canteen,
+                week,
+                validators: validators.clone(),
+                menu: week_menu.clone(),
+            };
+
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = store.save(&stored) {
+                    log::warn!("Can not store the menu of ({canteen}, {week:?}): {e}");
+                }
+            });
+        }
 
+        self.put(canteen, monday, week_menu.clone(), validators);
+
+        week_menu
+    }
+
+    fn put(
+        &self,
+        canteen: Canteen,
+        monday: NaiveDate,
+        week_menu: WeekMenu,
+        validators: Validators,
+    ) {
         self.cache
             .lock()
-            .map_err(|e| {
-                log::warn!("Can not access cache: {}", e.to_string());
-                e
-            })
+            .inspect_err(|e| log::warn!("Can not access cache: {e}"))
             .ok()
             .and_then(|mut cache| {
                 let entry = CacheEntry {
-                    val: menu.clone(),
-                    created: std::time::Instant::now(),
+                    created: week_menu.fetched_at(),
+                    val: week_menu,
                     fresh_dur: self.cache_fresh_dur,
+                    validators,
                 };
 
-                cache.put((canteen, day), entry)
+                cache.put((canteen, monday), entry)
             });
+    }
+}
+
+#[async_trait]
+impl MenuProvider for MenuCache {
+    async fn fetch_weekly_menu(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+    ) -> anyhow::Result<WeekMenu> {
+        self.fetch_weekly_menu_on(canteen, week, Local::now().date_naive())
+            .await
+    }
+
+    async fn fetch_daily_menu(
+        &self,
+        day: chrono::NaiveDate,
+        canteen: Canteen,
+    ) -> anyhow::Result<Menu> {
+        let week = MenuWeek::for_date(day, chrono::Local::now().date_naive())
+            .ok_or(FetcherError::NotPublished { canteen, date: day })?;
+        let week_menu = self.fetch_weekly_menu(canteen, week).await?;
+        let is_weekday = day.weekday().num_days_from_monday() < 5;
+
+        if is_weekday && week_menu.is_menu_missing(day) {
+            self.check_weekday_has_dishes(day, canteen)?;
+        }
+
+        let menu = menu_of_day(&week_menu, day, canteen)?;
+
+        if is_weekday && menu.dishes().next().is_none() {
+            self.check_weekday_has_dishes(day, canteen)?;
+        }
 
         Ok(menu)
     }
+
+    fn metrics(&self) -> Option<FetchMetricsSnapshot> {
+        self.inner.metrics()
+    }
 }
 
-impl Default for HtmlMenuFetcherWithCache {
-    fn default() -> Self {
-        Self::new()
+/// Turns an error shared between coalesced calls back into an owned one, keeping its type if it
+/// is a [`FetcherError`].
+fn unshare_error(e: Arc<anyhow::Error>) -> anyhow::Error {
+    match e.downcast_ref::<FetcherError>() {
+        Some(e) => e.clone().into(),
+        None => anyhow::anyhow!("{e:#}"),
     }
 }
 
 #[derive(Debug, PartialEq, Hash, Clone)]
 struct CacheEntry<V> {
     val: V,
-    created: std::time::Instant,
+    created: DateTime<Utc>,
     fresh_dur: std::time::Duration,
+    validators: Validators,
 }
 
 impl<V> CacheEntry<V> {
+    fn age(&self) -> std::time::Duration {
+        (Utc::now() - self.created).to_std().unwrap_or_default()
+    }
+
     fn is_fresh(&self) -> bool {
-        self.created.elapsed() <= self.fresh_dur
+        self.age() <= self.fresh_dur
     }
 
     fn is_stale(&self) -> bool {
         !self.is_fresh()
     }
 
-    fn get_val(&self) -> &V {
-        &self.val
-    }
-
-    fn get_val_mut(&mut self) -> &mut V {
-        &mut self.val
+    /// Returns for how long the entry has been stale.
+    fn staleness(&self) -> std::time::Duration {
+        self.age().saturating_sub(self.fresh_dur)
     }
 }
 
 mod builder {}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::BTreeMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use chrono::{Datelike, Local, NaiveDate, Utc};
+    use strum::IntoEnumIterator;
+
+    use super::MenuCache;
+    use crate::domain::{
+        fetch::{
+            err::FetcherError, HtmlMenuFetcher, InMemoryMenuProvider, MenuProvider, MenuWeek,
+            RetryPolicy,
+        },
+        model::{menu::MenuExtra, Canteen, DayMenu, Menu, WeekMenu},
+    };
+
+    fn week_menu() -> WeekMenu {
+        let today = Local::now().date_naive();
+        let menu = Menu::new::<MenuExtra>(BTreeMap::new(), vec![]);
+
+        WeekMenu::new(BTreeMap::from([(today, DayMenu::Open(menu))]))
+    }
+
+    /// Serves the menus of `inner` only after `delay`, so that concurrent fetches overlap.
+    #[derive(Debug)]
+    struct SlowMenuProvider {
+        inner: InMemoryMenuProvider,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl MenuProvider for SlowMenuProvider {
+        async fn fetch_weekly_menu(
+            &self,
+            canteen: Canteen,
+            week: MenuWeek,
+        ) -> anyhow::Result<WeekMenu> {
+            tokio::time::sleep(self.delay).await;
+            self.inner.fetch_weekly_menu(canteen, week).await
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_coalesces_concurrent_misses_into_one_upstream_call() {
+        let upstream = InMemoryMenuProvider::new();
+        upstream.insert(Canteen::Vita, MenuWeek::Current, week_menu());
+        let cache = MenuCache::new(Arc::new(SlowMenuProvider {
+            inner: upstream.clone(),
+            delay: Duration::from_millis(100),
+        }));
+
+        for (canteen, found) in [(Canteen::Vita, true), (Canteen::Süd, false)] {
+            let fetches: Vec<_> = (0..8)
+                .map(|_| {
+                    let cache = cache.clone();
+                    tokio::spawn(async move {
+                        cache.fetch_weekly_menu(canteen, MenuWeek::Current).await
+                    })
+                })
+                .collect();
+            for fetch in fetches {
+                assert_eq!(fetch.await.unwrap().is_ok(), found);
+            }
+        }
+
+        // One call for the menu of the vita, one for the missing menu of the südpark
+        assert_eq!(upstream.fetch_count(), 2);
+    }
+
+    /// Starts a proxy that refuses every tunnel after a delay and returns its URL and the number
+    /// of tunnels it was asked for.
+    async fn slow_refusing_proxy() -> (String, Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let tunnels = Arc::new(AtomicUsize::new(0));
+
+        let counter = tunnels.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0; 1024];
+                    let _ = stream.read(&mut buf).await;
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    let _ = stream
+                        .write_all(b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n")
+                        .await;
+                });
+            }
+        });
+
+        (format!("http://{addr}"), tunnels)
+    }
+
+    #[tokio::test]
+    async fn it_coalesces_concurrent_misses_into_one_fetch() {
+        let (proxy, tunnels) = slow_refusing_proxy().await;
+        let client = reqwest::Client::builder()
+            .proxy(reqwest::Proxy::all(proxy).unwrap())
+            .build()
+            .unwrap();
+        let fetcher = HtmlMenuFetcher::with_client(client).with_retry_policy(RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        });
+        let cache = MenuCache::new(Arc::new(fetcher));
+
+        let calls: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    cache
+                        .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+                        .await
+                })
+            })
+            .collect();
+        for call in calls {
+            assert!(call.await.unwrap().is_err());
+        }
+
+        assert_eq!(tunnels.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.metrics().unwrap().requests, 1);
+    }
+
+    #[tokio::test]
+    async fn it_serves_fresh_menus_from_the_cache() {
+        let upstream = InMemoryMenuProvider::new();
+        upstream.insert(Canteen::Vita, MenuWeek::Current, week_menu());
+        let cache = MenuCache::new(Arc::new(upstream.clone()));
+
+        let fetches: Vec<_> = (0..10)
+            .map(|_| {
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    cache
+                        .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+                        .await
+                })
+            })
+            .collect();
+        for fetch in fetches {
+            assert!(fetch.await.unwrap().is_ok());
+        }
+        assert_eq!(upstream.fetch_count(), 1);
+
+        assert!(cache
+            .fetch_weekly_menu(Canteen::Süd, MenuWeek::Current)
+            .await
+            .is_err());
+        assert_eq!(upstream.fetch_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_refetches_the_current_week_after_sunday() {
+        let sunday = NaiveDate::from_ymd_opt(2024, 10, 13).unwrap();
+        let monday = sunday + chrono::Days::new(1);
+
+        let upstream = InMemoryMenuProvider::new();
+        upstream.insert(Canteen::Vita, MenuWeek::Current, week_menu());
+        upstream.insert(Canteen::Vita, MenuWeek::Next, week_menu());
+        let cache = MenuCache::new(Arc::new(upstream.clone()));
+
+        for _ in 0..2 {
+            cache
+                .fetch_weekly_menu_on(Canteen::Vita, MenuWeek::Current, sunday)
+                .await
+                .unwrap();
+        }
+        assert_eq!(upstream.fetch_count(), 1);
+
+        // Last week's menu is still fresh, but it is not the current week anymore
+        cache
+            .fetch_weekly_menu_on(Canteen::Vita, MenuWeek::Current, monday)
+            .await
+            .unwrap();
+        assert_eq!(upstream.fetch_count(), 2);
+
+        // This week's next week is next week's current week
+        cache
+            .fetch_weekly_menu_on(Canteen::Vita, MenuWeek::Next, monday)
+            .await
+            .unwrap();
+        assert_eq!(upstream.fetch_count(), 3);
+        cache
+            .fetch_weekly_menu_on(
+                Canteen::Vita,
+                MenuWeek::Current,
+                monday + chrono::Days::new(7),
+            )
+            .await
+            .unwrap();
+        assert_eq!(upstream.fetch_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn it_checks_empty_weekdays_against_cached_menus_only() {
+        let today = Local::now().date_naive();
+        let monday = today - chrono::Days::new(today.weekday().num_days_from_monday().into());
+        let empty_monday = || {
+            let menu = Menu::new::<MenuExtra>(BTreeMap::new(), vec![]);
+            WeekMenu::new(BTreeMap::from([(monday, DayMenu::Open(menu))]))
+        };
+
+        let upstream = InMemoryMenuProvider::new();
+        for canteen in Canteen::iter() {
+            upstream.insert(canteen, MenuWeek::Current, empty_monday());
+        }
+        let cache = MenuCache::new(Arc::new(upstream.clone()));
+
+        // The other canteens are not cached, so they are neither fetched nor held against it
+        assert!(cache.fetch_daily_menu(monday, Canteen::Vita).await.is_ok());
+        assert_eq!(upstream.fetch_count(), 1);
+
+        for canteen in Canteen::iter() {
+            cache
+                .fetch_weekly_menu(canteen, MenuWeek::Current)
+                .await
+                .unwrap();
+        }
+        let err = cache
+            .fetch_daily_menu(monday, Canteen::Vita)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(FetcherError::LayoutChanged { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_checks_weekdays_closed_for_lack_of_a_menu() {
+        let today = Local::now().date_naive();
+        let monday = today - chrono::Days::new(today.weekday().num_days_from_monday().into());
+        let closed_monday = |menu_missing: bool| {
+            WeekMenu::new(BTreeMap::from([(monday, DayMenu::Closed)]))
+                .with_missing_menus(menu_missing.then_some(monday).into_iter().collect())
+        };
+
+        for (menu_missing, layout_changed) in [(false, false), (true, true)] {
+            let upstream = InMemoryMenuProvider::new();
+            for canteen in Canteen::iter() {
+                upstream.insert(canteen, MenuWeek::Current, closed_monday(menu_missing));
+            }
+            let cache = MenuCache::new(Arc::new(upstream));
+            for canteen in Canteen::iter() {
+                cache
+                    .fetch_weekly_menu(canteen, MenuWeek::Current)
+                    .await
+                    .unwrap();
+            }
+
+            let err = cache
+                .fetch_daily_menu(monday, Canteen::Vita)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                matches!(err.downcast_ref(), Some(FetcherError::LayoutChanged { .. })),
+                layout_changed,
+                "{err}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn it_serves_stale_menus_while_refreshing_them() {
+        let upstream = InMemoryMenuProvider::new();
+        upstream.insert(Canteen::Vita, MenuWeek::Current, week_menu());
+        let cache = MenuCache::new(Arc::new(upstream.clone())).with_fresh_dur(Duration::ZERO);
+
+        let first = cache
+            .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+            .await
+            .unwrap();
+        assert!(!first.is_stale());
+
+        let second = cache
+            .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+            .await
+            .unwrap();
+        assert!(second.is_stale());
+        assert!(second.fetched_at() <= Utc::now());
+
+        // The background refresh
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(upstream.fetch_count(), 2);
+    }
+}
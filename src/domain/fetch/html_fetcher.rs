@@ -1,122 +1,286 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Instant,
+};
 
 use anyhow::anyhow;
-use chrono::NaiveDate;
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
+use itertools::Itertools;
+use reqwest::{header, StatusCode};
 use scraper::{ElementRef, Html};
-use strum::EnumCount;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::model::{
-    menu::{Dish, Label, Menu, MenuExtra},
-    Canteen,
+    menu::{Category, DayMenu, Dish, Label, Menu, MenuExtra, Price, SideDish, WeekMenu},
+    Additive, Allergen, Canteen, Climate, ClimateLabel, Nutrition,
 };
 
-use super::err::FetcherError;
+use super::{
+    err::FetcherError,
+    retry::{CircuitBreaker, FetchMetrics, FetchMetricsSnapshot, RetryPolicy},
+    HttpConfig, MenuProvider, MenuWeek, ParseIssue, ParseReport, RateLimiter,
+};
 
-fn menu_url(canteen: Canteen) -> &'static str {
-    lazy_static! {
-        static ref URLS: [&'static str; Canteen::COUNT] = init_menu_urls();
-    }
+/// Returns the URL of the page of `canteen` for `week` under `base_url`.
+fn menu_url(base_url: &str, canteen: Canteen, week: MenuWeek) -> String {
+    // current week pages end in "-w", next week pages in "-n"
+    let week_suffix = match week {
+        MenuWeek::Current => "w",
+        MenuWeek::Next => "n",
+    };
 
-    fn init_menu_urls() -> [&'static str; Canteen::COUNT] {
-        fn format_url(s: &str) -> String {
-            format!(
-                "https://www.studierendenwerk-aachen.de/speiseplaene/{}-w.html",
-                s
-            )
-        }
+    format!(
+        "{}/{}-{}.html",
+        base_url.trim_end_matches('/'),
+        canteen.slug(),
+        week_suffix
+    )
+}
 
-        fn string_to_static_str(s: String) -> &'static str {
-            Box::leak(s.into_boxed_str())
-        }
+/// The `ETag` and `Last-Modified` headers of a response, used to revalidate it later on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
 
-        let arr = [
-            "academica",
-            "ahornstrasse",
-            "bayernallee",
-            "templergraben",
-            "eupenerstrasse",
-            "juelich",
-            "kmac",
-            "suedpark",
-            "vita",
-        ]
-        .map(|s: &str| string_to_static_str(format_url(s)));
-
-        arr
-    }
-
-    let idx = match canteen {
-        Canteen::Academica => 0,
-        Canteen::Ahorn => 1,
-        Canteen::Bayernallee => 2,
-        Canteen::Bistro => 3,
-        Canteen::Eupener => 4,
-        Canteen::Jülich => 5,
-        Canteen::KMAC => 6,
-        Canteen::Süd => 7,
-        Canteen::Vita => 8,
-    };
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
 
-    URLS[idx]
+/// Outcome of a conditional request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Revalidated<T> {
+    /// The cached value is still up to date
+    NotModified,
+    Modified {
+        val: T,
+        validators: Validators,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub struct HtmlMenuFetcher {
     http: reqwest::Client,
+    base_url: String,
+    limiter: RateLimiter,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+    metrics: Arc<FetchMetrics>,
 }
 
 impl HtmlMenuFetcher {
     pub fn new() -> Self {
-        Self::with_client(reqwest::Client::new())
+        // expect: the default config has no proxy that could be invalid
+        Self::from_config(&HttpConfig::default()).expect("Can not build the HTTP client")
+    }
+
+    pub fn from_config(config: &HttpConfig) -> anyhow::Result<Self> {
+        Ok(Self::with_client(config.build_client()?)
+            .with_base_url(config.base_url.clone())
+            .with_rate_limiter(config.rate_limiter()))
     }
 
     pub fn with_client(client: reqwest::Client) -> Self {
-        Self { http: client }
+        Self {
+            http: client,
+            base_url: HttpConfig::default().base_url,
+            limiter: RateLimiter::default(),
+            retry: RetryPolicy::default(),
+            breaker: CircuitBreaker::default(),
+            metrics: Arc::default(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Sets the limiter of requests upstream, which is shared by all clones of the fetcher.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Parses every day section of a week page.
+    ///
+    /// Parsing is lenient: rows and tables that can not be parsed are left out of the menu and
+    /// listed in the returned report instead.
+    pub(crate) fn parse_week(&self, menu_html: &Html) -> (WeekMenu, ParseReport) {
+        let mut days = BTreeMap::new();
+        let mut missing_menus = BTreeSet::new();
+        let mut report = ParseReport::default();
+
+        for section in menu_html.select(&selectors::DAILY_MENU_WRAPPER) {
+            let title: String = section
+                .select(&selectors::DATE_TITLE)
+                .flat_map(|elm| elm.text())
+                .collect();
+
+            let Some(date) = re::DATE_REGEX
+                .find(&title)
+                .and_then(|m| NaiveDate::parse_from_str(m.as_str(), "%d.%m.%Y").ok())
+            else {
+                report.push(ParseIssue::UndatedSection {
+                    title: title.trim().to_owned(),
+                });
+                continue;
+            };
+
+            let menu_container = section
+                .children()
+                .filter_map(ElementRef::wrap)
+                .find(|e| selectors::DIV.matches(e));
+
+            // A closed canteen says so in a note instead of the menu table
+            let has_note = menu_container
+                .is_some_and(|container| container.select(&selectors::NOTE).next().is_some());
+
+            let day_menu = match menu_container
+                .and_then(|container| self.parse_menu(container, date, &mut report))
+            {
+                Some(menu) => DayMenu::Open(menu),
+                None if has_note => DayMenu::Closed,
+                None => {
+                    report.push(ParseIssue::MissingTable {
+                        date,
+                        class: "menues",
+                    });
+                    missing_menus.insert(date);
+                    DayMenu::Closed
+                }
+            };
+
+            days.insert(date, day_menu);
+        }
+
+        (
+            WeekMenu::new(days).with_missing_menus(missing_menus),
+            report,
+        )
     }
 
-    pub async fn fetch_daily_menu(
+    /// Fetches `url`, retrying transient errors unless the circuit of its host is open.
+    async fn fetch_html(
         &self,
-        day: chrono::NaiveDate,
-        canteen: Canteen,
-    ) -> anyhow::Result<Menu> {
-        let menu_html = self.fetch_html(menu_url(canteen)).await?;
-
-        let matching_menu_container = menu_html
-            .select(&selectors::DAILY_MENU_WRAPPER)
-            .filter(|elm| {
-                elm.select(&selectors::DATE_TITLE)
-                    .flat_map(|elm| elm.text())
-                    .next()
-                    .and_then(|text| re::DATE_REGEX.find(text))
-                    .and_then(|m| NaiveDate::parse_from_str(m.as_str(), "%d.%m.%Y").ok())
-                    .map(|section_date| section_date == day)
-                    .unwrap_or(false)
-            })
-            .flat_map(|e| e.children())
-            .filter_map(|node| ElementRef::wrap(node))
-            .filter(|e| selectors::DIV.matches(e))
-            .next()
-            .ok_or(FetcherError::CanteenClosed {
-                canteen: canteen,
-                date: day,
-            })?;
+        url: &str,
+        validators: &Validators,
+    ) -> Result<Revalidated<Html>, FetcherError> {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .unwrap_or_default();
+
+        self.breaker
+            .check(&host, Instant::now())
+            .inspect_err(|_| self.metrics.inc_short_circuited())?;
+
+        let mut attempt = 1;
+        loop {
+            self.metrics.inc_requests();
+
+            let err = match self.fetch_html_once(url, validators).await {
+                Ok(html) => {
+                    if self.breaker.record_success(&host) {
+                        log::info!("Circuit for {host} is closed again");
+                    }
+                    return Ok(html);
+                }
+                Err(e) if !e.is_transient() => return Err(e),
+                Err(e) => e,
+            };
+
+            self.metrics.inc_failures();
+
+            if self.breaker.record_failure(&host, Instant::now()) {
+                self.metrics.inc_circuit_opened();
+                log::warn!(
+                    "Opened circuit for {host} after repeated failures: {err}. {:?}",
+                    self.metrics.snapshot()
+                );
+                return Err(err);
+            }
+
+            if attempt >= self.retry.max_attempts {
+                log::warn!("Giving up on {url} after {attempt} attempt(s): {err}");
+                return Err(err);
+            }
 
-        self.parse_menu(matching_menu_container)
+            let delay = self.retry.backoff(attempt);
+            log::info!("Attempt {attempt} to fetch {url} failed: {err}. Retrying in {delay:?}");
+
+            self.metrics.inc_retries();
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
     }
 
-    async fn fetch_html(&self, url: &str) -> anyhow::Result<Html> {
-        let resp_text = self.http.get(url).send().await?.text().await?;
-        Ok(Html::parse_document(&resp_text))
+    async fn fetch_html_once(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<Revalidated<Html>, FetcherError> {
+        let _permit = self.limiter.acquire().await;
+
+        let mut req = self.http.get(url);
+        if let Some(etag) = &validators.etag {
+            req = req.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let resp = req.send().await?;
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            return Ok(Revalidated::NotModified);
+        }
+
+        let resp = resp.error_for_status()?;
+        let header_value = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let validators = Validators {
+            etag: header_value(header::ETAG),
+            last_modified: header_value(header::LAST_MODIFIED),
+        };
+
+        let resp_text = resp.text().await?;
+
+        Ok(Revalidated::Modified {
+            val: Html::parse_document(&resp_text),
+            validators,
+        })
     }
 
-    fn parse_menu(&self, container: ElementRef) -> anyhow::Result<Menu> {
+    /// Parses the menu of one day. A day without a menu table is a day without food.
+    fn parse_menu(
+        &self,
+        container: ElementRef,
+        date: NaiveDate,
+        report: &mut ParseReport,
+    ) -> Option<Menu> {
         let table_elms = container
             .children()
-            .filter_map(|e| ElementRef::wrap(e))
-            .filter(|e| {
-                let e = e.value();
-                e.name().to_ascii_lowercase() == "table"
-            });
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name().eq_ignore_ascii_case("table"));
 
         let (menu_table, extras_table) = table_elms.fold((None, None), |(menus, extras), e| {
             let elm = e.value();
@@ -129,41 +293,56 @@ impl HtmlMenuFetcher {
             }
         });
 
-        let menu_table = menu_table.ok_or(FetcherError::ElementNotFound {
-            tag: "table".to_owned(),
-            cls: vec!["menues".to_owned()],
-        })?;
-
-        let extras_table = extras_table.ok_or(FetcherError::ElementNotFound {
-            tag: "table".to_owned(),
-            cls: vec!["extras".to_owned()],
-        })?;
+        let dishes = self.parse_menu_table(menu_table?, date, report);
 
-        let dishes = self.parse_menu_table(menu_table);
-        let extras = self.parse_extras_table(extras_table)?;
+        let extras = match extras_table {
+            Some(extras_table) => self.parse_extras_table(extras_table, date, report),
+            None => {
+                report.push(ParseIssue::MissingTable {
+                    date,
+                    class: "extras",
+                });
+                vec![]
+            }
+        };
 
-        Ok(Menu::new(dishes, extras))
+        Some(Menu::new(dishes, extras))
     }
 
-    fn parse_menu_table(&self, table: ElementRef) -> HashMap<String, Vec<Dish>> {
+    fn parse_menu_table(
+        &self,
+        table: ElementRef,
+        date: NaiveDate,
+        report: &mut ParseReport,
+    ) -> BTreeMap<Category, Vec<Dish>> {
         lazy_static! {
             static ref ROW: scraper::Selector = scraper::Selector::parse("tbody > tr").unwrap();
         }
 
-        let dishes: HashMap<String, Vec<Dish>> = table
-            .select(&ROW)
-            .filter_map(|tr| self.parse_menu_dish(tr).ok())
-            .fold(HashMap::new(), |acc, val| {
-                let (cat, dish) = val;
-                let mut map = acc;
-                map.entry(cat).or_insert(vec![]).push(dish);
-                map
-            });
+        let mut dishes: BTreeMap<Category, Vec<Dish>> = BTreeMap::new();
+
+        for tr in table.select(&ROW) {
+            for cls in tr.value().classes().filter(|cls| !is_known_row_class(cls)) {
+                report.push(ParseIssue::UnknownLabelClass {
+                    date,
+                    class: cls.to_owned(),
+                });
+            }
+
+            match self.parse_menu_dish(tr) {
+                Ok((cat, dish)) => dishes.entry(cat).or_default().push(dish),
+                Err(e) => report.push(ParseIssue::SkippedRow {
+                    date,
+                    table: "menues",
+                    reason: e.to_string(),
+                }),
+            }
+        }
 
         dishes
     }
 
-    fn parse_menu_dish(&self, tr: ElementRef) -> anyhow::Result<(String, Dish)> {
+    fn parse_menu_dish(&self, tr: ElementRef) -> anyhow::Result<(Category, Dish)> {
         lazy_static! {
             static ref CATEGORY: scraper::Selector =
                 scraper::Selector::parse("span.menue-category").unwrap();
@@ -180,55 +359,59 @@ impl HtmlMenuFetcher {
             .text()
             .next()
             .ok_or(anyhow!(".menu-category contains no text node"))?
-            .trim()
-            .split_ascii_whitespace()
-            .next()
-            // unwrap: split yields at least one element
+            .split_whitespace()
+            .join(" ")
+            .parse::<Category>()
+            // unwrap: unknown categories fall back to Category::Other
             .unwrap();
 
-        let dish: String = tr
+        let dish_descr = tr
             .select(&DISH_DESCR)
             .next()
-            .ok_or(anyhow!("No span with class \"menue-descr\""))?
+            .ok_or(anyhow!("No span with class \"menue-descr\""))?;
+
+        let dish: String = dish_descr
             .children()
             .filter_map(|node| node.value().as_text())
             .map(|txt_node| <str as AsRef<str>>::as_ref(txt_node))
             .collect();
 
-        let mut dish_iter = dish.split("|").map(|s| s.trim());
+        let (allergens, additives) = parse_markers(dish_descr);
+        let nutrition = parse_nutrition(dish_descr);
+        let climate = parse_climate(tr, dish_descr);
+
+        // Collapse the whitespace left behind by the removed markers
+        let mut dish_iter = dish
+            .split('|')
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "));
 
         // unwrap: split yields at least one element
         let dish_name = dish_iter.next().unwrap();
 
-        let dish_descs: Vec<String> = dish_iter.map(|s| s.to_owned()).collect();
+        let dish_descs: Vec<String> = dish_iter.filter(|s| !s.is_empty()).collect();
 
         let price = tr
             .select(&PRICE)
             .next()
-            .and_then(|elm| elm.text().next())
-            .map(|text| text.to_owned());
-
-        let labels: Vec<_> = tr
-            .value()
-            .classes()
-            .filter_map(|cls| match cls {
-                "Fisch" => Some(Label::Fish),
-                "OLV" => Some(Label::Veggie),
-                "vegan" => Some(Label::Vegan),
-                "Geflügel" => Some(Label::Chicken),
-                "Schwein" => Some(Label::Pork),
-                "Rind" => Some(Label::Beef),
-                _ => None,
-            })
-            .collect();
+            .and_then(|elm| parse_price(&elm.text().collect::<String>()));
+
+        let labels: Vec<_> = tr.value().classes().filter_map(parse_label).collect();
 
         Ok((
-            category.to_owned(),
-            Dish::new(dish_name.to_owned(), dish_descs, labels, price),
+            category,
+            Dish::new(dish_name, dish_descs, labels, price)
+                .with_markers(allergens, additives)
+                .with_nutrition(nutrition)
+                .with_climate(climate),
         ))
     }
 
-    fn parse_extras_table(&self, table: ElementRef) -> anyhow::Result<Vec<MenuExtra>> {
+    fn parse_extras_table(
+        &self,
+        table: ElementRef,
+        date: NaiveDate,
+        report: &mut ParseReport,
+    ) -> Vec<MenuExtra> {
         lazy_static! {
             static ref ROW: scraper::Selector =
                 scraper::Selector::parse("tbody tr .menue-wrapper").unwrap();
@@ -243,51 +426,353 @@ impl HtmlMenuFetcher {
 
                 let category = cells
                     .clone()
-                    .filter(|elm| {
+                    .find(|elm| {
                         elm.value().has_class(
                             "menue-category",
                             scraper::CaseSensitivity::AsciiCaseInsensitive,
                         )
                     })
-                    .next()
                     .and_then(|elm| elm.text().next())
                     .map(|text| text.trim())
                     // Fallback to the empty string as a category if none is given
                     .unwrap_or("")
                     .to_owned();
 
-                let extras = cells
+                let desc = cells
                     .clone()
-                    .filter(|elm| {
+                    .find(|elm| {
                         elm.value()
                             .has_class("menue-desc", scraper::CaseSensitivity::AsciiCaseInsensitive)
                     })
-                    .next()
-                    .ok_or(anyhow!("No span with class \"menue-desc\""))?
-                    .children()
-                    .filter_map(|node| node.value().as_text())
-                    .map(|txt_node| txt_node.trim().to_string())
-                    .collect::<Vec<_>>();
-
-                let extras = match extras.len().checked_sub(1).map(|idx| extras.split_at(idx)) {
-                    None => extras.join(""),
-                    Some((init, last)) => init.join(", ") + " oder " + &last[0],
-                };
+                    .ok_or(anyhow!("No span with class \"menue-desc\""))?;
+
+                let extras = parse_side_dishes(desc);
 
                 Ok(MenuExtra::new(category, extras))
             })
+            .filter_map(|extra| {
+                extra
+                    .inspect_err(|e| {
+                        report.push(ParseIssue::SkippedRow {
+                            date,
+                            table: "extras",
+                            reason: e.to_string(),
+                        })
+                    })
+                    .ok()
+            })
             .collect()
     }
 }
 
+#[async_trait]
+impl MenuProvider for HtmlMenuFetcher {
+    /// Fetches the page of `canteen` for `week` and parses every day section on it.
+    async fn fetch_weekly_menu(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+    ) -> anyhow::Result<WeekMenu> {
+        match self
+            .fetch_weekly_menu_if_modified(canteen, week, &Validators::default())
+            .await?
+        {
+            Revalidated::Modified { val, .. } => Ok(val),
+            // Without validators the page can not be unmodified
+            Revalidated::NotModified => Err(FetcherError::HttpStatus(304).into()),
+        }
+    }
+
+    fn metrics(&self) -> Option<FetchMetricsSnapshot> {
+        Some(self.metrics.snapshot())
+    }
+
+    /// Fetches the page of `canteen` for `week` unless it still matches `validators`.
+    ///
+    /// Only a modified page is parsed again.
+    async fn fetch_weekly_menu_if_modified(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        validators: &Validators,
+    ) -> anyhow::Result<Revalidated<WeekMenu>> {
+        let Revalidated::Modified {
+            val: menu_html,
+            validators,
+        } = self
+            .fetch_html(&menu_url(&self.base_url, canteen, week), validators)
+            .await?
+        else {
+            log::info!("The {week:?} week menu of {canteen} is not modified");
+            return Ok(Revalidated::NotModified);
+        };
+
+        let (week_menu, report) = self.parse_week(&menu_html);
+        if !report.is_empty() {
+            log::warn!("Parsing the {week:?} week menu of {canteen} was incomplete:\n{report}");
+        }
+
+        if let Some(reason) = detect_layout_change(&menu_html, &week_menu) {
+            return Err(FetcherError::LayoutChanged { canteen, reason }.into());
+        }
+
+        Ok(Revalidated::Modified {
+            val: week_menu,
+            validators,
+        })
+    }
+}
+
+/// Returns why the page no longer looks like a menu page, if it does not.
+///
+/// An empty accordion is how a week without a published menu looks, so only a missing accordion,
+/// day sections without a single date or a week whose weekdays are all closed, some of them for
+/// lack of a menu table, count as a changed layout.
+fn detect_layout_change(menu_html: &Html, week_menu: &WeekMenu) -> Option<String> {
+    let has_sections = menu_html
+        .select(&selectors::DAILY_MENU_WRAPPER)
+        .next()
+        .is_some();
+
+    if menu_html.select(&selectors::ACCORDION).next().is_none() && !has_sections {
+        Some("the page contains no menu accordion".to_owned())
+    } else if has_sections && week_menu.is_empty() {
+        Some("no day section has a parseable date".to_owned())
+    } else if weekdays_lack_menus(week_menu) {
+        Some("no weekday section has a menu table".to_owned())
+    } else {
+        None
+    }
+}
+
+/// Returns whether no weekday of the week is open and some are closed for lack of a menu table.
+fn weekdays_lack_menus(week_menu: &WeekMenu) -> bool {
+    let weekdays: Vec<_> = week_menu
+        .days()
+        .filter(|(date, _)| date.weekday().num_days_from_monday() < 5)
+        .collect();
+
+    weekdays
+        .iter()
+        .all(|(_, day)| matches!(day, DayMenu::Closed))
+        && weekdays
+            .iter()
+            .any(|(date, _)| week_menu.is_menu_missing(**date))
+}
+
+fn parse_climate_label(cls: &str) -> Option<ClimateLabel> {
+    match cls.to_lowercase().as_str() {
+        "klimateller" => Some(ClimateLabel::ClimatePlate),
+        _ => None,
+    }
+}
+
+/// Returns whether `cls` is a class of dish rows that is either a label or used for styling.
+fn is_known_row_class(cls: &str) -> bool {
+    const STYLE_CLASSES: [&str; 2] = ["odd", "even"];
+
+    STYLE_CLASSES.contains(&cls) || parse_label(cls).is_some() || parse_climate_label(cls).is_some()
+}
+
+fn parse_label(cls: &str) -> Option<Label> {
+    match cls {
+        "Fisch" => Some(Label::Fish),
+        "OLV" => Some(Label::Veggie),
+        "vegan" => Some(Label::Vegan),
+        "Geflügel" => Some(Label::Chicken),
+        "Schwein" => Some(Label::Pork),
+        "Rind" => Some(Label::Beef),
+        _ => None,
+    }
+}
+
+/// Parses the options of an extras cell, e.g. "Pommes<sup>L</sup><span class="seperator">oder</span>Reis".
+///
+/// Every text node is an option, markers belong to the option before them. Options wrapped in
+/// their own element take their labels from its classes.
+fn parse_side_dishes(desc: ElementRef) -> Vec<SideDish> {
+    let mut options: Vec<SideDish> = vec![];
+
+    for node in desc.children() {
+        if let Some(text) = node.value().as_text() {
+            let name = text.split_whitespace().join(" ");
+            if !name.is_empty() {
+                options.push(SideDish::new(name));
+            }
+            continue;
+        }
+
+        let Some(elm) = ElementRef::wrap(node) else {
+            continue;
+        };
+
+        if selectors::MARKER.matches(&elm) {
+            if let Some(option) = options.last_mut() {
+                let (allergens, additives) = parse_marker_codes(elm.text());
+                option.add_markers(allergens, additives);
+            }
+        } else if !selectors::SEPARATOR.matches(&elm) {
+            let name = elm
+                .children()
+                .filter_map(|node| node.value().as_text())
+                .flat_map(|text| text.split_whitespace())
+                .join(" ");
+            if name.is_empty() {
+                continue;
+            }
+
+            let labels = elm.value().classes().filter_map(parse_label).collect();
+            let mut option = SideDish::new(name).with_labels(labels);
+            let (allergens, additives) = parse_markers(elm);
+            option.add_markers(allergens, additives);
+
+            options.push(option);
+        }
+    }
+
+    options
+}
+
+/// Collects the allergen and additive codes from the `<sup>` markers below `elm`.
+///
+/// The codes are sorted and deduplicated. Unknown codes are skipped.
+fn parse_markers(elm: ElementRef) -> (Vec<Allergen>, Vec<Additive>) {
+    parse_marker_codes(elm.select(&selectors::MARKER).flat_map(|sup| sup.text()))
+}
+
+/// Parses comma separated allergen and additive codes, e.g. "A,A1,2".
+fn parse_marker_codes<'a>(texts: impl Iterator<Item = &'a str>) -> (Vec<Allergen>, Vec<Additive>) {
+    let mut allergens = BTreeSet::new();
+    let mut additives = BTreeSet::new();
+
+    let codes = texts
+        .flat_map(|text| text.split(','))
+        .map(str::trim)
+        .filter(|code| !code.is_empty());
+
+    for code in codes {
+        if let Ok(allergen) = code.parse::<Allergen>() {
+            allergens.insert(allergen);
+        } else if let Ok(additive) = code.parse::<Additive>() {
+            additives.insert(additive);
+        } else {
+            log::debug!("Unknown allergen or additive code \"{code}\"");
+        }
+    }
+
+    (
+        allergens.into_iter().collect(),
+        additives.into_iter().collect(),
+    )
+}
+
+/// Parses the nutrition block below `elm`, e.g. "Brennwert = 2807 kJ (671 kcal)", "Fett = 22,2g".
+fn parse_nutrition(elm: ElementRef) -> Option<Nutrition> {
+    // Parses a German decimal number into thousandths, e.g. "22,2" into 22200
+    fn parse_milli(num: &str) -> Option<u32> {
+        let (int, frac) = num.split_once(',').unwrap_or((num, ""));
+        let frac = format!("{:0<3}", frac.get(..3).unwrap_or(frac));
+
+        int.parse::<u32>()
+            .ok()?
+            .checked_mul(1000)?
+            .checked_add(frac.parse().ok()?)
+    }
+
+    let text: String = elm
+        .select(&selectors::NUTRITION)
+        .flat_map(|e| e.text())
+        .collect();
+
+    let mut nutrition = Nutrition::default();
+
+    if let Some(caps) = re::ENERGY_REGEX.captures(&text) {
+        nutrition.energy_kj = parse_milli(&caps[1]).map(|kj| kj / 1000);
+        nutrition.energy_kcal = caps
+            .get(2)
+            .and_then(|m| parse_milli(m.as_str()))
+            .map(|kcal| kcal / 1000);
+    }
+
+    for caps in re::NUTRIENT_REGEX.captures_iter(&text) {
+        let mg = parse_milli(&caps[2]);
+        match &caps[1] {
+            "Fett" => nutrition.fat_mg = mg,
+            "Kohlenhydrate" => nutrition.carbs_mg = mg,
+            "Eiweiß" => nutrition.protein_mg = mg,
+            _ => {}
+        }
+    }
+
+    (!nutrition.is_empty()).then_some(nutrition)
+}
+
+/// Parses the CO₂ footprint from the nutrition block below `descr`, e.g. "CO2 = 1.234 g",
+/// and climate badges from the classes of the dish row `tr`.
+fn parse_climate(tr: ElementRef, descr: ElementRef) -> Option<Climate> {
+    let text: String = descr
+        .select(&selectors::NUTRITION)
+        .flat_map(|e| e.text())
+        .collect();
+
+    let co2_grams = re::CO2_REGEX
+        .captures(&text)
+        .and_then(|caps| caps[1].replace('.', "").parse().ok());
+
+    let labels = tr
+        .value()
+        .classes()
+        .filter_map(parse_climate_label)
+        .collect();
+
+    let climate = Climate { co2_grams, labels };
+    (!climate.is_empty()).then_some(climate)
+}
+
+impl Default for HtmlMenuFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses the prices of a dish in German decimal format, e.g. "2,20 € / 4,20 €".
+///
+/// The amounts are given in the order students, employees, guests. Amounts too large to count in
+/// cents are left out, without shifting the others to another tier.
+fn parse_price(text: &str) -> Option<Price> {
+    let mut amounts = re::PRICE_REGEX.captures_iter(text).map(|caps| {
+        let euros: u32 = caps.get(1)?.as_str().parse().ok()?;
+        let cents: u32 = match caps.get(2) {
+            None => 0,
+            // "2,5" means 2,50 €
+            Some(m) if m.len() == 1 => m.as_str().parse::<u32>().ok()? * 10,
+            Some(m) => m.as_str().parse().ok()?,
+        };
+
+        euros.checked_mul(100)?.checked_add(cents)
+    });
+
+    let student = amounts.next()??;
+    Some(Price::new(
+        student,
+        amounts.next().flatten(),
+        amounts.next().flatten(),
+    ))
+}
+
 pub mod selectors {
     use scraper::Selector;
 
     lazy_static! {
+        pub static ref ACCORDION: Selector = Selector::parse("body div.accordion").unwrap();
         pub static ref DAILY_MENU_WRAPPER: Selector =
             Selector::parse("body div.accordion > div").unwrap();
         pub static ref DATE_TITLE: Selector = Selector::parse("h3 > a").unwrap();
         pub static ref DIV: Selector = Selector::parse("div").unwrap();
+        pub static ref MARKER: Selector = Selector::parse("sup").unwrap();
+        pub static ref SEPARATOR: Selector = Selector::parse(".seperator, .separator").unwrap();
+        pub static ref NUTRITION: Selector = Selector::parse(".nutr-info").unwrap();
+        pub static ref NOTE: Selector = Selector::parse("#note").unwrap();
     }
 }
 
@@ -296,5 +781,430 @@ pub mod re {
 
     lazy_static! {
         pub static ref DATE_REGEX: Regex = Regex::new(r"(\d{2}\.\d{2}\.\d{4})").unwrap();
+        pub static ref PRICE_REGEX: Regex = Regex::new(r"(\d+)(?:,(\d{1,2}))?\s*€").unwrap();
+        pub static ref ENERGY_REGEX: Regex =
+            Regex::new(r"Brennwert\s*=\s*(\d+(?:,\d+)?)\s*kJ(?:\s*\((\d+(?:,\d+)?)\s*kcal\))?")
+                .unwrap();
+        pub static ref NUTRIENT_REGEX: Regex =
+            Regex::new(r"(Fett|Kohlenhydrate|Eiweiß)\s*=\s*(\d+(?:,\d+)?)\s*g").unwrap();
+        pub static ref CO2_REGEX: Regex =
+            Regex::new(r"CO(?:2|₂)\s*=\s*(\d+(?:\.\d{3})*)\s*g").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use scraper::Html;
+
+    use crate::domain::model::{
+        menu::{Category, Label, Price, PriceTier},
+        Additive, Allergen, ClimateLabel, DayMenu,
+    };
+
+    use super::{
+        detect_layout_change, parse_price, FetcherError, HtmlMenuFetcher, ParseIssue, RetryPolicy,
+        Revalidated, Validators,
+    };
+    use crate::domain::{
+        fetch::{stub_server, HttpConfig, MenuProvider, MenuWeek},
+        model::Canteen,
+    };
+
+    const ACADEMICA_WEEK: &str = include_str!("../../../fixtures/html/academica-w.html");
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 10, d).unwrap()
+    }
+
+    #[test]
+    fn it_parses_all_days_of_a_week_page() {
+        let html = Html::parse_document(ACADEMICA_WEEK);
+        let (week, _) = HtmlMenuFetcher::new().parse_week(&html);
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected a menu on monday");
+        };
+        let monday = monday.fmt_html(PriceTier::Student).unwrap();
+        assert!(monday.contains("Hähnchenbrust in Currysauce"));
+        assert!(monday.contains("Gemüse-Linsen-Curry"));
+
+        assert!(matches!(week.day(date(15)), Some(DayMenu::Closed)));
+        assert!(matches!(week.day(date(16)), Some(DayMenu::Open(_))));
+        assert!(week.day(date(17)).is_none());
+    }
+
+    #[test]
+    fn it_parses_german_prices() {
+        assert_eq!(parse_price("2,20 €"), Some(Price::new(220, None, None)));
+        assert_eq!(
+            parse_price("2,20 € / 4,2 € / 5 €"),
+            Some(Price::new(220, Some(420), Some(500)))
+        );
+        assert_eq!(parse_price("ausverkauft"), None);
+        // 50 000 000 € do not fit in cents, the other tiers keep their amounts
+        assert_eq!(
+            parse_price("2,20 € / 50000000,00 € / 5 €"),
+            Some(Price::new(220, None, Some(500)))
+        );
+        assert_eq!(parse_price("99999999999999999999,99 € / 4,20 €"), None);
+
+        let price = parse_price("2,60 € / 4,60 €").unwrap();
+        assert_eq!(price.get(PriceTier::Employee), Some(460));
+        assert_eq!(price.get(PriceTier::Guest), None);
+        assert_eq!(price.to_string(), "2,60 €");
+    }
+
+    #[test]
+    fn it_parses_allergens_and_additives() {
+        let html = Html::parse_document(ACADEMICA_WEEK);
+        let (week, _) = HtmlMenuFetcher::new().parse_week(&html);
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected a menu on monday");
+        };
+        let dish = monday
+            .dishes()
+            .find(|dish| dish.name() == "Hähnchenbrust in Currysauce")
+            .expect("dish name should not contain markers");
+
+        assert_eq!(
+            dish.allergens(),
+            &[
+                Allergen::Gluten,
+                Allergen::Wheat,
+                Allergen::Milk,
+                Allergen::Mustard
+            ]
+        );
+        assert_eq!(dish.additives(), &[Additive::Preservative]);
+        assert!(!dish.contains_allergen(Allergen::Fish));
+    }
+
+    #[test]
+    fn it_parses_nutrition_facts() {
+        let html = Html::parse_document(ACADEMICA_WEEK);
+        let (week, _) = HtmlMenuFetcher::new().parse_week(&html);
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected a menu on monday");
+        };
+        let mut dishes = monday.dishes();
+        let curry = dishes
+            .find(|dish| dish.name() == "Hähnchenbrust in Currysauce")
+            .unwrap();
+
+        let nutrition = curry.nutrition().unwrap();
+        assert_eq!(nutrition.energy_kj, Some(2807));
+        assert_eq!(nutrition.energy_kcal, Some(671));
+        assert_eq!(nutrition.fat_mg, Some(22_200));
+        assert_eq!(nutrition.carbs_mg, Some(72_500));
+        assert_eq!(nutrition.protein_mg, Some(42_300));
+        assert_eq!(
+            nutrition.fmt_html().unwrap(),
+            "671 kcal · Fett 22,2 g · Kohlenhydrate 72,5 g · Eiweiß 42,3 g"
+        );
+
+        assert!(monday
+            .dishes()
+            .find(|dish| dish.name() == "Gemüse-Linsen-Curry")
+            .unwrap()
+            .nutrition()
+            .is_none());
+    }
+
+    #[test]
+    fn it_skips_nutrition_values_out_of_range() {
+        let html = Html::parse_fragment(
+            r#"<div class="nutr-info">Brennwert = 9999999 kJ (671 kcal) Fett = 4294968 g</div>"#,
+        );
+
+        let nutrition = super::parse_nutrition(html.root_element()).unwrap();
+        assert_eq!(nutrition.energy_kj, None);
+        assert_eq!(nutrition.energy_kcal, Some(671));
+        assert_eq!(nutrition.fat_mg, None);
+    }
+
+    #[test]
+    fn it_parses_climate_data() {
+        let html = Html::parse_document(ACADEMICA_WEEK);
+        let (week, _) = HtmlMenuFetcher::new().parse_week(&html);
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected a menu on monday");
+        };
+
+        let by_footprint: Vec<_> = monday
+            .dishes_by_footprint()
+            .into_iter()
+            .map(|dish| (dish.name(), dish.co2_grams()))
+            .collect();
+        assert_eq!(
+            by_footprint,
+            [
+                ("Gemüse-Linsen-Curry", Some(412)),
+                ("Hähnchenbrust in Currysauce", Some(1234))
+            ]
+        );
+
+        let curry = monday.dishes_by_footprint()[0];
+        assert_eq!(
+            curry.climate().unwrap().labels,
+            [ClimateLabel::ClimatePlate]
+        );
+    }
+
+    #[test]
+    fn it_parses_categories() {
+        assert_eq!(
+            "Burger Classics".parse::<Category>(),
+            Ok(Category::BurgerClassic)
+        );
+        assert_eq!(
+            "Burger der Woche".parse::<Category>(),
+            Ok(Category::BurgerWeekly)
+        );
+        assert_eq!(
+            "Ofenkartoffel".parse::<Category>(),
+            Ok(Category::Other("Ofenkartoffel".to_owned()))
+        );
+        assert_eq!(
+            Category::Other("Ofenkartoffel".to_owned()).to_string(),
+            "Ofenkartoffel"
+        );
+        assert!(Category::Wok < Category::Other("Aal".to_owned()));
+    }
+
+    #[test]
+    fn it_parses_side_dishes() {
+        let html = Html::parse_document(ACADEMICA_WEEK);
+        let (week, _) = HtmlMenuFetcher::new().parse_week(&html);
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected a menu on monday");
+        };
+        let main_sides = &monday.extras()[0];
+
+        let names: Vec<_> = main_sides.options().iter().map(|o| o.name()).collect();
+        assert_eq!(names, ["Pommes frites", "Reis", "Salzkartoffeln"]);
+        assert_eq!(
+            main_sides.options()[0].additives(),
+            &[Additive::Sulphurated]
+        );
+        assert_eq!(main_sides.options()[1].labels(), &[Label::Vegan]);
+        assert_eq!(
+            main_sides.fmt_html().unwrap(),
+            "<em>Hauptbeilagen</em>: Pommes frites, Reis oder Salzkartoffeln"
+        );
+        assert_eq!(
+            monday.extras()[1].fmt_html().unwrap(),
+            "<em>Nebenbeilage</em>: Brokkoli"
+        );
+    }
+
+    #[test]
+    fn it_reports_what_it_could_not_parse() {
+        let html = Html::parse_document(ACADEMICA_WEEK);
+        let (_, report) = HtmlMenuFetcher::new().parse_week(&html);
+        assert!(report.is_empty(), "{report}");
+
+        let html = Html::parse_document(
+            r##"<body><div class="accordion">
+              <div>
+                <h3><a href="#">Montag, 14.10.2024</a></h3>
+                <div>
+                  <table class="menues"><tbody>
+                    <tr class="odd Halal">
+                      <td>
+                        <span class="menue-category">Tellergericht</span>
+                        <span class="menue-desc"><span class="expand-nutr">Falafel</span></span>
+                        <span class="menue-price">2,20 €</span>
+                      </td>
+                    </tr>
+                    <tr class="even"><td><span class="menue-desc">Suppe</span></td></tr>
+                  </tbody></table>
+                </div>
+              </div>
+              <div><h3><a href="#">Demnächst</a></h3></div>
+            </div></body>"##,
+        );
+        let (week, report) = HtmlMenuFetcher::new().parse_week(&html);
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected a menu on monday");
+        };
+        assert_eq!(monday.dishes().count(), 1);
+        assert!(monday.extras().is_empty());
+
+        let issues = report.issues();
+        assert_eq!(issues.len(), 4, "{report}");
+        assert!(issues.contains(&ParseIssue::UnknownLabelClass {
+            date: date(14),
+            class: "Halal".to_owned()
+        }));
+        assert!(issues.contains(&ParseIssue::MissingTable {
+            date: date(14),
+            class: "extras"
+        }));
+        assert!(issues.contains(&ParseIssue::UndatedSection {
+            title: "Demnächst".to_owned()
+        }));
+        assert!(matches!(
+            issues
+                .iter()
+                .find(|i| matches!(i, ParseIssue::SkippedRow { .. })),
+            Some(ParseIssue::SkippedRow {
+                table: "menues",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_reports_days_without_a_menu_table() {
+        let html = Html::parse_document(
+            r##"<body><div class="accordion">
+              <div><h3><a>Montag, 14.10.2024</a></h3><div><table class="gerichte"></table></div></div>
+              <div><h3><a>Dienstag, 15.10.2024</a></h3><div><div id="note">Geschlossen</div></div></div>
+            </div></body>"##,
+        );
+        let (week, report) = HtmlMenuFetcher::new().parse_week(&html);
+
+        assert!(matches!(week.day(date(14)), Some(DayMenu::Closed)));
+        assert!(week.is_menu_missing(date(14)));
+        assert_eq!(
+            report.issues(),
+            [ParseIssue::MissingTable {
+                date: date(14),
+                class: "menues"
+            }]
+        );
+
+        // The canteen announced that it is closed
+        assert!(matches!(week.day(date(15)), Some(DayMenu::Closed)));
+        assert!(!week.is_menu_missing(date(15)));
+    }
+
+    #[test]
+    fn it_detects_a_changed_page_layout() {
+        let detect = |page: &str| {
+            let html = Html::parse_document(page);
+            let (week, _) = HtmlMenuFetcher::new().parse_week(&html);
+            detect_layout_change(&html, &week)
+        };
+
+        assert_eq!(detect(ACADEMICA_WEEK), None);
+        // Weeks without a published menu have an empty accordion
+        assert_eq!(
+            detect(r#"<body><div class="accordion"></div></body>"#),
+            None
+        );
+        assert!(detect(r#"<body><main class="speiseplan"></main></body>"#).is_some());
+        // Every weekday lost its menu table
+        assert!(
+            detect(&ACADEMICA_WEEK.replace(r#"class="menues""#, r#"class="gerichte""#)).is_some()
+        );
+        assert!(detect(
+            r#"<body><div class="accordion"><div><h3><a>Montag</a></h3></div></div></body>"#
+        )
+        .is_some());
+    }
+
+    #[tokio::test]
+    async fn it_maps_http_failures_to_fetcher_errors() {
+        let fetcher = HtmlMenuFetcher::with_client(
+            reqwest::Client::builder()
+                .timeout(std::time::Duration::from_millis(500))
+                .build()
+                .unwrap(),
+        )
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        });
+
+        let url =
+            stub_server::serve(|_| "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n")
+                .await;
+        assert!(matches!(
+            fetcher.fetch_html(&url, &Validators::default()).await,
+            Err(FetcherError::HttpStatus(503))
+        ));
+
+        let url = stub_server::serve(|_| "").await;
+        assert!(matches!(
+            fetcher.fetch_html(&url, &Validators::default()).await,
+            Err(FetcherError::Timeout)
+        ));
+
+        // Nothing listens on the discard port
+        assert!(matches!(
+            fetcher
+                .fetch_html("http://127.0.0.1:9/", &Validators::default())
+                .await,
+            Err(FetcherError::Network(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_revalidates_with_validators() {
+        let respond = |req: &str| {
+            if req.to_lowercase().contains("if-none-match: \"v1\"") {
+                "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\n\r\n"
+            } else {
+                "HTTP/1.1 200 OK\r\netag: \"v1\"\r\n\
+                last-modified: Mon, 14 Oct 2024 06:00:00 GMT\r\n\
+                content-length: 13\r\n\r\n<html></html>"
+            }
+        };
+        let fetcher = HtmlMenuFetcher::new();
+
+        let url = stub_server::serve(respond).await;
+        let Ok(Revalidated::Modified { validators, .. }) =
+            fetcher.fetch_html(&url, &Validators::default()).await
+        else {
+            panic!("expected the page to be fetched");
+        };
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            validators.last_modified.as_deref(),
+            Some("Mon, 14 Oct 2024 06:00:00 GMT")
+        );
+
+        let url = stub_server::serve(respond).await;
+        assert!(matches!(
+            fetcher.fetch_html(&url, &validators).await,
+            Ok(Revalidated::NotModified)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_fetches_from_the_configured_base_url() {
+        let respond = |req: &str| {
+            let req = req.to_lowercase();
+            if req.starts_with("get /mirror/academica-w.html ")
+                && req.contains("user-agent: rwth-fressbot/")
+            {
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{ACADEMICA_WEEK}",
+                    ACADEMICA_WEEK.len()
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n".to_owned()
+            }
+        };
+        let url = stub_server::serve(respond).await;
+
+        let fetcher = HtmlMenuFetcher::from_config(&HttpConfig {
+            base_url: format!("{url}mirror/"),
+            ..HttpConfig::default()
+        })
+        .unwrap();
+        let week = fetcher
+            .fetch_weekly_menu(Canteen::Academica, MenuWeek::Current)
+            .await
+            .unwrap();
+
+        assert!(matches!(week.day(date(14)), Some(DayMenu::Open(_))));
     }
 }
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use super::RateLimiter;
+
+pub const DEFAULT_BASE_URL: &str = "https://www.studierendenwerk-aachen.de/speiseplaene/";
+
+/// Settings of the HTTP client that fetches the menu pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpConfig {
+    /// URL the menu pages are found under, e.g. a local mirror
+    pub base_url: String,
+    pub user_agent: String,
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+    /// Proxy all requests are sent through
+    pub proxy: Option<String>,
+    pub requests_per_sec: f64,
+    pub max_connections: usize,
+}
+
+impl HttpConfig {
+    /// Returns the default user agent, naming `contact` for the operators of the site.
+    pub fn user_agent_with_contact(contact: Option<&str>) -> String {
+        let agent = concat!("rwth-fressbot/", env!("CARGO_PKG_VERSION"));
+
+        match contact {
+            Some(contact) => format!("{agent} (+{contact})"),
+            None => agent.to_owned(),
+        }
+    }
+
+    pub fn build_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout);
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Returns a limiter that allows bursts as large as the connection limit.
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(
+            self.requests_per_sec,
+            self.max_connections as u32,
+            self.max_connections,
+        )
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            user_agent: Self::user_agent_with_contact(None),
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(10),
+            proxy: None,
+            requests_per_sec: 2.0,
+            max_connections: 4,
+        }
+    }
+}
@@ -1,7 +1,84 @@
 mod cache;
 mod html_fetcher;
-pub use cache::HtmlMenuFetcherWithCache;
-pub use html_fetcher::HtmlMenuFetcher;
+mod http_config;
+mod openmensa;
+mod prefetch;
+mod provider;
+mod rate_limit;
+mod report;
+mod retry;
+mod single_flight;
+mod store;
+#[cfg(test)]
+mod stub_server;
+pub use cache::MenuCache;
+pub use html_fetcher::{HtmlMenuFetcher, Revalidated, Validators};
+pub use http_config::HttpConfig;
+pub use openmensa::{default_canteen_id, OpenMensaConfig, OpenMensaFetcher, OpenMensaUsage};
+pub use prefetch::{PrefetchSchedule, Prefetcher};
+pub use provider::{FallbackMenuProvider, InMemoryMenuProvider, MenuProvider};
+pub use rate_limit::{RateLimiter, RatePermit};
+pub use report::{ParseIssue, ParseReport};
+pub use retry::{CircuitBreaker, FetchMetricsSnapshot, RetryPolicy};
+pub use single_flight::SingleFlight;
+pub use store::{JsonDirStore, MenuStore, StoredWeekMenu};
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumCount;
+
+use crate::domain::model::{Canteen, DayMenu, Menu, WeekMenu};
+
+/// The week pages the Studierendenwerk publishes for every canteen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumCount, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuWeek {
+    Current,
+    Next,
+}
+
+impl MenuWeek {
+    /// Returns the page that covers `date` as seen from `today`, if any.
+    pub fn for_date(date: NaiveDate, today: NaiveDate) -> Option<Self> {
+        let weeks_ahead = (week_start(date) - week_start(today)).num_weeks();
+        match weeks_ahead {
+            0 => Some(Self::Current),
+            1 => Some(Self::Next),
+            _ => None,
+        }
+    }
+
+    /// Returns the Monday of the week this page covers as seen from `today`.
+    pub fn monday(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Current => week_start(today),
+            Self::Next => week_start(today) + chrono::Days::new(7),
+        }
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - chrono::Days::new(date.weekday().num_days_from_monday().into())
+}
+
+/// Picks the menu of `day` out of `week_menu`, treating days without a menu as closed.
+///
+/// The menu of a stale week menu is marked as stale as well.
+///
+/// A page without any day sections has not been published yet.
+fn menu_of_day(week_menu: &WeekMenu, day: NaiveDate, canteen: Canteen) -> anyhow::Result<Menu> {
+    match week_menu.day(day) {
+        Some(DayMenu::Open(menu)) => Ok(menu
+            .clone()
+            .with_stale_since(week_menu.is_stale().then(|| week_menu.fetched_at()))),
+        None if week_menu.is_empty() => {
+            Err(err::FetcherError::NotPublished { canteen, date: day }.into())
+        }
+        Some(DayMenu::Closed) | None => {
+            Err(err::FetcherError::CanteenClosed { canteen, date: day }.into())
+        }
+    }
+}
 
 pub mod err {
     use chrono::NaiveDate;
@@ -14,7 +91,103 @@ pub mod err {
         #[error("canteen {canteen} is closed on date {}", .date.format("%Y-%m-%d"))]
         CanteenClosed { canteen: Canteen, date: NaiveDate },
 
-        #[error("No element {tag} with class(es) {:?}", &.cls[..])]
-        ElementNotFound { tag: String, cls: Vec<String> },
+        #[error("no menu of canteen {canteen} is published for date {}", .date.format("%Y-%m-%d"))]
+        NotPublished { canteen: Canteen, date: NaiveDate },
+
+        #[error("canteen {canteen} is not available from this menu source")]
+        UnsupportedCanteen { canteen: Canteen },
+
+        #[error("the menu page of canteen {canteen} changed its layout: {reason}")]
+        LayoutChanged { canteen: Canteen, reason: String },
+
+        #[error("the request to the menu page timed out")]
+        Timeout,
+
+        #[error("the menu page responded with HTTP status {0}")]
+        HttpStatus(u16),
+
+        #[error("can not reach the menu page: {0}")]
+        Network(String),
+
+        #[error("can not read the menu page: {0}")]
+        Parse(String),
+
+        #[error("requests to {host} are paused after repeated failures")]
+        CircuitOpen { host: String },
+    }
+
+    impl FetcherError {
+        /// Returns whether retrying the request may succeed.
+        pub fn is_transient(&self) -> bool {
+            match self {
+                FetcherError::Timeout | FetcherError::Network(_) => true,
+                FetcherError::HttpStatus(status) => *status == 429 || *status >= 500,
+                _ => false,
+            }
+        }
+    }
+
+    impl From<reqwest::Error> for FetcherError {
+        fn from(e: reqwest::Error) -> Self {
+            if e.is_timeout() {
+                FetcherError::Timeout
+            } else if let Some(status) = e.status() {
+                FetcherError::HttpStatus(status.as_u16())
+            } else if e.is_decode() {
+                FetcherError::Parse(e.to_string())
+            } else {
+                FetcherError::Network(e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::{menu_of_day, MenuWeek};
+    use crate::domain::model::{menu::MenuExtra, Canteen, DayMenu, Menu, WeekMenu};
+
+    #[test]
+    fn it_picks_the_week_page_by_date() {
+        // Saturday
+        let today = NaiveDate::from_ymd_opt(2024, 10, 19).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+
+        assert_eq!(MenuWeek::for_date(date(14), today), Some(MenuWeek::Current));
+        assert_eq!(MenuWeek::for_date(date(20), today), Some(MenuWeek::Current));
+        assert_eq!(MenuWeek::for_date(date(21), today), Some(MenuWeek::Next));
+        assert_eq!(MenuWeek::for_date(date(28), today), None);
+        assert_eq!(MenuWeek::for_date(date(11), today), None);
+    }
+
+    #[test]
+    fn it_marks_menus_of_stale_weeks() {
+        let date = NaiveDate::from_ymd_opt(2024, 10, 14).unwrap();
+        let fetched_at = Utc.with_ymd_and_hms(2024, 10, 14, 9, 30, 0).unwrap();
+        let week = WeekMenu::new(BTreeMap::from([(
+            date,
+            DayMenu::Open(Menu::new::<MenuExtra>(BTreeMap::new(), vec![])),
+        )]))
+        .with_fetched_at(fetched_at);
+
+        let menu = menu_of_day(&week, date, Canteen::Academica).unwrap();
+        assert_eq!(menu.stale_since(), None);
+
+        let menu = menu_of_day(&week.into_stale(), date, Canteen::Academica).unwrap();
+        assert_eq!(menu.stale_since(), Some(fetched_at));
+        assert_eq!(
+            menu.fmt_stale_note_html(fetched_at + chrono::Duration::minutes(25))
+                .as_deref(),
+            Some("<i>⏱ Stand von vor 25 Min.</i>")
+        );
+        assert_eq!(
+            menu.fmt_stale_note_html(fetched_at + chrono::Duration::minutes(130))
+                .as_deref(),
+            Some("<i>⏱ Stand von vor 2 Std.</i>")
+        );
     }
 }
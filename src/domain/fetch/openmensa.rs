@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use crate::domain::model::{
+    menu::{Category, DayMenu, Dish, Label, Menu, MenuExtra, Price, WeekMenu},
+    Additive, Allergen, Canteen,
+};
+
+use super::{err::FetcherError, HttpConfig, MenuProvider, MenuWeek, RateLimiter};
+
+pub const DEFAULT_OPENMENSA_URL: &str = "https://openmensa.org/api/v2/";
+
+/// Returns the ID OpenMensa lists `canteen` under, if it lists it at all.
+///
+/// The IDs are taken from OpenMensa's list of canteens in Aachen and Jülich. They can be checked
+/// with `GET https://openmensa.org/api/v2/canteens?ids=94,95,96,97,98,100,101,187`, every canteen
+/// is shown on `https://openmensa.org/c/<id>`. IDs that change there can be overridden with
+/// `OPENMENSA_CANTEEN_IDS`.
+pub fn default_canteen_id(canteen: Canteen) -> Option<u32> {
+    match canteen {
+        Canteen::Academica => Some(187),
+        Canteen::Ahorn => Some(95),
+        Canteen::Bayernallee => Some(96),
+        Canteen::Bistro => Some(97),
+        Canteen::Eupener => Some(98),
+        Canteen::Jülich => Some(100),
+        Canteen::Süd => Some(101),
+        Canteen::Vita => Some(94),
+        // Not listed on OpenMensa
+        Canteen::KMAC => None,
+    }
+}
+
+/// Settings of the OpenMensa source.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OpenMensaConfig {
+    pub usage: OpenMensaUsage,
+    /// URL of the OpenMensa API, e.g. a local mirror
+    pub base_url: Option<String>,
+    /// IDs that replace the ones of [`default_canteen_id`]
+    pub canteen_ids: HashMap<Canteen, u32>,
+}
+
+/// Whether the menus are fetched from OpenMensa instead of or after the menu pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, strum_macros::EnumString)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum OpenMensaUsage {
+    #[default]
+    Off,
+    Primary,
+    Fallback,
+}
+
+/// Fetches the menus from the OpenMensa API.
+#[derive(Debug, Clone)]
+pub struct OpenMensaFetcher {
+    http: reqwest::Client,
+    base_url: String,
+    limiter: RateLimiter,
+    canteen_ids: HashMap<Canteen, u32>,
+}
+
+impl OpenMensaFetcher {
+    pub fn from_config(http: &HttpConfig, config: &OpenMensaConfig) -> anyhow::Result<Self> {
+        let mut fetcher = Self::with_client(http.build_client()?)
+            .with_rate_limiter(http.rate_limiter())
+            .with_canteen_ids(config.canteen_ids.clone());
+        if let Some(base_url) = &config.base_url {
+            fetcher = fetcher.with_base_url(base_url.clone());
+        }
+
+        Ok(fetcher)
+    }
+
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            http: client,
+            base_url: DEFAULT_OPENMENSA_URL.to_owned(),
+            limiter: RateLimiter::default(),
+            canteen_ids: HashMap::new(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Overrides the OpenMensa IDs of some canteens.
+    pub fn with_canteen_ids(mut self, canteen_ids: HashMap<Canteen, u32>) -> Self {
+        self.canteen_ids = canteen_ids;
+        self
+    }
+
+    fn canteen_id(&self, canteen: Canteen) -> Option<u32> {
+        self.canteen_ids
+            .get(&canteen)
+            .copied()
+            .or_else(|| default_canteen_id(canteen))
+    }
+
+    /// Fetches the menus of `canteen` for the week starting on `monday`.
+    ///
+    /// Days OpenMensa does not list are left out, closed days are not fetched.
+    async fn fetch_week(
+        &self,
+        canteen: Canteen,
+        monday: NaiveDate,
+    ) -> Result<WeekMenu, FetcherError> {
+        let id = self
+            .canteen_id(canteen)
+            .ok_or(FetcherError::UnsupportedCanteen { canteen })?;
+
+        let days: Vec<OpenMensaDay> = self
+            .get_json(&format!("canteens/{id}/days?start={monday}"))
+            .await?;
+
+        let mut week = BTreeMap::new();
+        for day in days
+            .into_iter()
+            .filter(|day| day.date >= monday && day.date < monday + chrono::Days::new(7))
+        {
+            if day.closed {
+                week.insert(day.date, DayMenu::Closed);
+                continue;
+            }
+
+            let meals: Vec<OpenMensaMeal> = self
+                .get_json(&format!("canteens/{id}/days/{}/meals", day.date))
+                .await?;
+            week.insert(day.date, DayMenu::Open(menu_of_meals(meals)));
+        }
+
+        Ok(WeekMenu::new(week))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<T, FetcherError> {
+        let url = format!("{}/{path}", self.base_url.trim_end_matches('/'));
+
+        let _permit = self.limiter.acquire().await;
+        let resp = self.http.get(&url).send().await?.error_for_status()?;
+
+        let body = resp.bytes().await?;
+
+        serde_json::from_slice(&body).map_err(|e| FetcherError::Parse(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl MenuProvider for OpenMensaFetcher {
+    async fn fetch_weekly_menu(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+    ) -> anyhow::Result<WeekMenu> {
+        let monday = week.monday(chrono::Local::now().date_naive());
+
+        Ok(self.fetch_week(canteen, monday).await?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenMensaDay {
+    date: NaiveDate,
+    closed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenMensaMeal {
+    name: String,
+    category: String,
+    #[serde(default)]
+    prices: OpenMensaPrices,
+    #[serde(default)]
+    notes: Vec<String>,
+}
+
+/// Prices in euros. Prices for pupils are not shown by the bot.
+#[derive(Debug, Default, Deserialize)]
+struct OpenMensaPrices {
+    students: Option<f64>,
+    employees: Option<f64>,
+    others: Option<f64>,
+}
+
+impl OpenMensaPrices {
+    /// Returns the prices in cents, if a student price is given.
+    fn to_price(&self) -> Option<Price> {
+        let cents = |euros: Option<f64>| euros.map(|euros| (euros * 100.0).round() as u32);
+
+        Some(Price::new(
+            cents(self.students)?,
+            cents(self.employees),
+            cents(self.others),
+        ))
+    }
+}
+
+fn menu_of_meals(meals: Vec<OpenMensaMeal>) -> Menu {
+    let mut dishes: BTreeMap<Category, Vec<Dish>> = BTreeMap::new();
+    for meal in meals {
+        // unwrap: unknown categories fall back to Category::Other
+        let category: Category = meal.category.parse().unwrap();
+        dishes.entry(category).or_default().push(dish_of_meal(meal));
+    }
+
+    Menu::new::<MenuExtra>(dishes, vec![])
+}
+
+/// Maps the notes of a meal to labels, allergens and additives by their German names.
+///
+/// Notes without a match are kept as the description of the dish.
+fn dish_of_meal(meal: OpenMensaMeal) -> Dish {
+    let mut labels = vec![];
+    let mut allergens = vec![];
+    let mut additives = vec![];
+    let mut descs = vec![];
+
+    for note in meal.notes {
+        let is_note = |name: &str| note.trim().eq_ignore_ascii_case(name);
+
+        if let Some(label) = parse_label_note(note.trim()) {
+            labels.push(label);
+        } else if let Some(allergen) = Allergen::iter().find(|a| is_note(&a.to_string())) {
+            allergens.push(allergen);
+        } else if let Some(additive) = Additive::iter().find(|a| is_note(&a.to_string())) {
+            additives.push(additive);
+        } else {
+            descs.push(note);
+        }
+    }
+
+    allergens.sort();
+    additives.sort();
+
+    Dish::new(meal.name, descs, labels, meal.prices.to_price()).with_markers(allergens, additives)
+}
+
+fn parse_label_note(note: &str) -> Option<Label> {
+    match note.to_lowercase().as_str() {
+        "rind" | "rindfleisch" => Some(Label::Beef),
+        "geflügel" | "hähnchen" => Some(Label::Chicken),
+        "fisch" => Some(Label::Fish),
+        "schwein" | "schweinefleisch" => Some(Label::Pork),
+        "vegan" => Some(Label::Vegan),
+        "vegetarisch" | "ovo-lacto-vegetarisch" => Some(Label::Veggie),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::domain::{
+        fetch::{err::FetcherError, stub_server},
+        model::{
+            menu::{Category, DayMenu, Label},
+            Additive, Allergen, Canteen, PriceTier,
+        },
+    };
+
+    use super::OpenMensaFetcher;
+
+    const DAYS: &str = include_str!("../../../fixtures/openmensa/days.json");
+    const MEALS_MONDAY: &str = include_str!("../../../fixtures/openmensa/meals-2024-10-14.json");
+    const MEALS_TUESDAY: &str = include_str!("../../../fixtures/openmensa/meals-2024-10-15.json");
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 10, d).unwrap()
+    }
+
+    /// Serves `routes` by path on a local port and returns the API URL. Other paths are not found.
+    async fn serve_fixtures(routes: &'static [(&'static str, &'static str)]) -> String {
+        let url = stub_server::serve(move |req| {
+            let path = req.split_whitespace().nth(1).unwrap_or_default();
+            let path = path.split('?').next().unwrap_or_default();
+
+            match routes.iter().find(|(route, _)| *route == path) {
+                Some((_, body)) => format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                    content-length: {}\r\n\r\n{body}",
+                    body.len()
+                ),
+                None => "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n".to_owned(),
+            }
+        })
+        .await;
+
+        format!("{url}api/v2/")
+    }
+
+    #[tokio::test]
+    async fn it_maps_openmensa_meals_to_menus() {
+        let url = serve_fixtures(&[
+            ("/api/v2/canteens/187/days", DAYS),
+            ("/api/v2/canteens/187/days/2024-10-14/meals", MEALS_MONDAY),
+            ("/api/v2/canteens/187/days/2024-10-15/meals", MEALS_TUESDAY),
+        ])
+        .await;
+        let fetcher = OpenMensaFetcher::with_client(reqwest::Client::new()).with_base_url(url);
+
+        let week = fetcher
+            .fetch_week(Canteen::Academica, date(14))
+            .await
+            .unwrap();
+
+        assert!(matches!(week.day(date(16)), Some(DayMenu::Closed)));
+        // The next week is left out
+        assert!(week.day(date(21)).is_none());
+
+        let Some(DayMenu::Open(monday)) = week.day(date(14)) else {
+            panic!("expected the canteen to be open on monday");
+        };
+        let dishes: Vec<_> = monday.dishes().collect();
+        assert_eq!(dishes.len(), 3);
+
+        let curry = dishes
+            .iter()
+            .find(|dish| dish.name() == "Linsen-Curry mit Basmatireis")
+            .unwrap();
+        assert_eq!(curry.allergens(), &[Allergen::Celery, Allergen::Mustard]);
+        assert_eq!(curry.additives(), &[Additive::Colouring]);
+        assert_eq!(
+            curry.fmt_html(PriceTier::Employee).unwrap(),
+            "<strong>Linsen-Curry mit Basmatireis</strong> 🥑 – <strong>3,90 €</strong>"
+        );
+
+        let chicken = dishes
+            .iter()
+            .find(|dish| dish.name() == "Hähnchenbrust mit Rahmsoße")
+            .unwrap();
+        assert_eq!(chicken.allergens(), &[Allergen::Wheat, Allergen::Milk]);
+        assert_eq!(
+            chicken.fmt_html(PriceTier::Guest).unwrap(),
+            format!(
+                "<strong>Hähnchenbrust mit Rahmsoße</strong> | scharf {} – <strong>5,20 €</strong>",
+                Label::Chicken
+            )
+        );
+
+        let Some(DayMenu::Open(tuesday)) = week.day(date(15)) else {
+            panic!("expected the canteen to be open on tuesday");
+        };
+        assert!(tuesday
+            .fmt_html(PriceTier::Student)
+            .unwrap()
+            .starts_with(&format!("<em>{}</em>", Category::Classic)));
+    }
+
+    #[tokio::test]
+    async fn it_fails_for_canteens_missing_on_openmensa() {
+        let url = serve_fixtures(&[]).await;
+        let fetcher = OpenMensaFetcher::with_client(reqwest::Client::new()).with_base_url(url);
+
+        assert!(matches!(
+            fetcher.fetch_week(Canteen::KMAC, date(14)).await,
+            Err(FetcherError::UnsupportedCanteen {
+                canteen: Canteen::KMAC
+            })
+        ));
+        assert!(matches!(
+            fetcher.fetch_week(Canteen::Vita, date(14)).await,
+            Err(FetcherError::HttpStatus(404))
+        ));
+    }
+}
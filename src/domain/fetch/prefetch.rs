@@ -0,0 +1,208 @@
+use std::{str::FromStr, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use chrono::{Datelike, Duration, Local, NaiveDateTime, NaiveTime, Weekday};
+use strum::IntoEnumIterator;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::domain::model::Canteen;
+
+use super::{MenuProvider, MenuWeek};
+
+/// The times on weekdays at which all canteens are prefetched.
+///
+/// Parses from `"HH:MM-HH:MM/MINUTES"`, e.g. `"06:30-14:30/30"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefetchSchedule {
+    pub start: NaiveTime,
+    /// Not before `start`, runs do not wrap around midnight
+    pub end: NaiveTime,
+    pub interval: Duration,
+}
+
+impl PrefetchSchedule {
+    /// Returns the first run after `now`, in local time.
+    pub fn next_run(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let mut date = now.date();
+
+        loop {
+            if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                let mut run = date.and_time(self.start);
+                while run.time() <= self.end && run.date() == date {
+                    if run > now {
+                        return run;
+                    }
+                    run += self.interval;
+                }
+            }
+
+            // unwrap: dates do not run out any time soon
+            date = date.succ_opt().unwrap();
+        }
+    }
+}
+
+impl Default for PrefetchSchedule {
+    fn default() -> Self {
+        Self {
+            // unwrap: valid times
+            start: NaiveTime::from_hms_opt(6, 30, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
+            interval: Duration::minutes(30),
+        }
+    }
+}
+
+impl FromStr for PrefetchSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (times, interval) = s
+            .split_once('/')
+            .ok_or(anyhow!("missing \"/\" before the interval"))?;
+        let (start, end) = times
+            .split_once('-')
+            .ok_or(anyhow!("missing \"-\" between start and end"))?;
+
+        let parse_time = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .with_context(|| format!("invalid time {t}"))
+        };
+        let minutes: i64 = interval
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid interval {interval}"))?;
+
+        if minutes <= 0 {
+            return Err(anyhow!("the interval must be positive"));
+        }
+
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+        if start > end {
+            return Err(anyhow!("the start must not be after the end"));
+        }
+
+        Ok(Self {
+            start,
+            end,
+            interval: Duration::minutes(minutes),
+        })
+    }
+}
+
+/// Warms the cache by fetching the menus of all canteens on a schedule.
+#[derive(Debug, Clone)]
+pub struct Prefetcher {
+    fetcher: Arc<dyn MenuProvider>,
+    schedule: PrefetchSchedule,
+    concurrency: usize,
+}
+
+impl Prefetcher {
+    /// Prefetches from `fetcher`, usually a [`MenuCache`](super::MenuCache) to warm.
+    pub fn new(fetcher: Arc<dyn MenuProvider>, schedule: PrefetchSchedule) -> Self {
+        Self {
+            fetcher,
+            schedule,
+            concurrency: 3,
+        }
+    }
+
+    /// Sets how many menus are fetched at the same time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Runs the prefetcher in a background task until the task is aborted.
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let now = Local::now().naive_local();
+                let next_run = self.schedule.next_run(now);
+                log::debug!("Next prefetch at {next_run}");
+
+                tokio::time::sleep((next_run - now).to_std().unwrap_or_default()).await;
+                self.run_once().await;
+            }
+        })
+    }
+
+    /// Fetches the current week menu of every canteen.
+    pub async fn run_once(&self) {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut fetches = JoinSet::new();
+
+        for canteen in Canteen::iter() {
+            let fetcher = self.fetcher.clone();
+            let permits = permits.clone();
+
+            fetches.spawn(async move {
+                // unwrap: the semaphore is never closed
+                let _permit = permits.acquire().await.unwrap();
+                let res = fetcher.fetch_weekly_menu(canteen, MenuWeek::Current).await;
+                (canteen, res)
+            });
+        }
+
+        let mut failed = 0;
+        while let Some(joined) = fetches.join_next().await {
+            match joined {
+                Ok((_, Ok(_))) => {}
+                Ok((canteen, Err(e))) => {
+                    failed += 1;
+                    log::warn!("Can not prefetch the menu of {canteen}: {e}");
+                }
+                Err(e) => {
+                    failed += 1;
+                    log::warn!("Prefetch task failed: {e}");
+                }
+            }
+        }
+
+        log::info!(
+            "Prefetched the menus of {} of {} canteens",
+            Canteen::iter().count() - failed,
+            Canteen::iter().count()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use super::PrefetchSchedule;
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        // October 2024: the 14th is a Monday
+        NaiveDate::from_ymd_opt(2024, 10, day)
+            .unwrap()
+            .and_time(time.parse().unwrap())
+    }
+
+    #[test]
+    fn it_plans_runs_on_weekdays_only() {
+        let schedule = PrefetchSchedule::default();
+
+        assert_eq!(schedule.next_run(at(14, "03:00:00")), at(14, "06:30:00"));
+        assert_eq!(schedule.next_run(at(14, "06:30:00")), at(14, "07:00:00"));
+        assert_eq!(schedule.next_run(at(14, "11:41:00")), at(14, "12:00:00"));
+        assert_eq!(schedule.next_run(at(14, "14:30:00")), at(15, "06:30:00"));
+        // Friday evening to Monday morning
+        assert_eq!(schedule.next_run(at(18, "15:00:00")), at(21, "06:30:00"));
+        assert_eq!(schedule.next_run(at(19, "11:00:00")), at(21, "06:30:00"));
+    }
+
+    #[test]
+    fn it_parses_schedules() {
+        assert_eq!(
+            "06:30-14:30/30".parse::<PrefetchSchedule>().unwrap(),
+            PrefetchSchedule::default()
+        );
+        assert!("06:30-14:30".parse::<PrefetchSchedule>().is_err());
+        assert!("06:30-14:30/0".parse::<PrefetchSchedule>().is_err());
+        assert!("14:30-06:30/30".parse::<PrefetchSchedule>().is_err());
+        assert!("6.30-14:30/30".parse::<PrefetchSchedule>().is_err());
+    }
+}
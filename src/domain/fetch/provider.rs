@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use crate::domain::model::{Canteen, Menu, WeekMenu};
+
+use super::{
+    err::FetcherError, menu_of_day, FetchMetricsSnapshot, MenuWeek, Revalidated, Validators,
+};
+
+/// A source of menus, e.g. the menu pages of the Studierendenwerk or a cache in front of them.
+#[async_trait]
+pub trait MenuProvider: Debug + Send + Sync {
+    /// Returns the menus of `canteen` for `week`.
+    async fn fetch_weekly_menu(&self, canteen: Canteen, week: MenuWeek)
+        -> anyhow::Result<WeekMenu>;
+
+    /// Returns the menus of `canteen` for `week` unless they still match `validators`.
+    ///
+    /// Providers without validators always return the menus.
+    async fn fetch_weekly_menu_if_modified(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        _validators: &Validators,
+    ) -> anyhow::Result<Revalidated<WeekMenu>> {
+        Ok(Revalidated::Modified {
+            val: self.fetch_weekly_menu(canteen, week).await?,
+            validators: Validators::default(),
+        })
+    }
+
+    /// Returns the menu of `canteen` on `day`.
+    async fn fetch_daily_menu(&self, day: NaiveDate, canteen: Canteen) -> anyhow::Result<Menu> {
+        let week = MenuWeek::for_date(day, chrono::Local::now().date_naive())
+            .ok_or(FetcherError::NotPublished { canteen, date: day })?;
+        let week_menu = self.fetch_weekly_menu(canteen, week).await?;
+
+        menu_of_day(&week_menu, day, canteen)
+    }
+
+    /// Returns the counters of the requests made upstream, if this provider keeps any.
+    fn metrics(&self) -> Option<FetchMetricsSnapshot> {
+        None
+    }
+}
+
+/// Serves menus from memory, e.g. to test handlers without the network.
+///
+/// Weeks without a menu fail with HTTP status 404. Clones share their menus.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryMenuProvider {
+    menus: Arc<Mutex<HashMap<(Canteen, MenuWeek), WeekMenu>>>,
+    fetches: Arc<AtomicUsize>,
+}
+
+impl InMemoryMenuProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, canteen: Canteen, week: MenuWeek, menu: WeekMenu) {
+        if let Ok(mut menus) = self.menus.lock() {
+            menus.insert((canteen, week), menu);
+        }
+    }
+
+    /// Returns how many week menus were fetched.
+    pub fn fetch_count(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl MenuProvider for InMemoryMenuProvider {
+    async fn fetch_weekly_menu(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+    ) -> anyhow::Result<WeekMenu> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+
+        self.menus
+            .lock()
+            .map_err(|e| anyhow::anyhow!("{e}"))?
+            .get(&(canteen, week))
+            .cloned()
+            .ok_or(FetcherError::HttpStatus(404).into())
+    }
+}
+
+/// Fetches menus from `primary` and from `fallback` whenever `primary` fails.
+#[derive(Debug, Clone)]
+pub struct FallbackMenuProvider {
+    primary: Arc<dyn MenuProvider>,
+    fallback: Arc<dyn MenuProvider>,
+}
+
+impl FallbackMenuProvider {
+    pub fn new(primary: Arc<dyn MenuProvider>, fallback: Arc<dyn MenuProvider>) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+#[async_trait]
+impl MenuProvider for FallbackMenuProvider {
+    async fn fetch_weekly_menu(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+    ) -> anyhow::Result<WeekMenu> {
+        match self.primary.fetch_weekly_menu(canteen, week).await {
+            Ok(week_menu) => Ok(week_menu),
+            Err(e) => {
+                log::warn!("Falling back for the {week:?} week menu of {canteen} - {e}");
+                let fallback = self.fallback.fetch_weekly_menu(canteen, week).await;
+                keep_primary_error_if_unsupported(fallback, e)
+            }
+        }
+    }
+
+    /// Revalidates with `primary` only, as the validators are those of its responses.
+    async fn fetch_weekly_menu_if_modified(
+        &self,
+        canteen: Canteen,
+        week: MenuWeek,
+        validators: &Validators,
+    ) -> anyhow::Result<Revalidated<WeekMenu>> {
+        match self
+            .primary
+            .fetch_weekly_menu_if_modified(canteen, week, validators)
+            .await
+        {
+            Ok(revalidated) => Ok(revalidated),
+            Err(e) => {
+                log::warn!("Falling back for the {week:?} week menu of {canteen} - {e}");
+                let fallback = self.fallback.fetch_weekly_menu(canteen, week).await;
+                Ok(Revalidated::Modified {
+                    val: keep_primary_error_if_unsupported(fallback, e)?,
+                    validators: Validators::default(),
+                })
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<FetchMetricsSnapshot> {
+        self.primary.metrics().or_else(|| self.fallback.metrics())
+    }
+}
+
+/// Fails with the error of the primary provider if the fallback does not know the canteen, as
+/// that error tells more about why there is no menu.
+fn keep_primary_error_if_unsupported(
+    fallback: anyhow::Result<WeekMenu>,
+    primary_err: anyhow::Error,
+) -> anyhow::Result<WeekMenu> {
+    fallback.map_err(|e| match e.downcast_ref::<FetcherError>() {
+        Some(FetcherError::UnsupportedCanteen { .. }) => primary_err,
+        _ => e,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use crate::domain::model::{Canteen, WeekMenu};
+
+    use super::{FallbackMenuProvider, InMemoryMenuProvider, MenuProvider, MenuWeek};
+    use crate::domain::fetch::{err::FetcherError, OpenMensaFetcher};
+
+    #[tokio::test]
+    async fn it_falls_back_when_the_primary_provider_fails() {
+        let primary = InMemoryMenuProvider::new();
+        let fallback = InMemoryMenuProvider::new();
+        fallback.insert(
+            Canteen::Vita,
+            MenuWeek::Current,
+            WeekMenu::new(BTreeMap::new()),
+        );
+        let provider =
+            FallbackMenuProvider::new(Arc::new(primary.clone()), Arc::new(fallback.clone()));
+
+        assert!(provider
+            .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+            .await
+            .is_ok());
+        assert_eq!((primary.fetch_count(), fallback.fetch_count()), (1, 1));
+
+        primary.insert(
+            Canteen::Vita,
+            MenuWeek::Current,
+            WeekMenu::new(BTreeMap::new()),
+        );
+        assert!(provider
+            .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+            .await
+            .is_ok());
+        assert_eq!((primary.fetch_count(), fallback.fetch_count()), (2, 1));
+
+        assert!(provider
+            .fetch_weekly_menu(Canteen::Academica, MenuWeek::Current)
+            .await
+            .is_err());
+
+        // OpenMensa does not list the KMAC, so the error of the primary provider tells more
+        let provider = FallbackMenuProvider::new(
+            Arc::new(primary),
+            Arc::new(OpenMensaFetcher::with_client(reqwest::Client::new())),
+        );
+        let err = provider
+            .fetch_weekly_menu(Canteen::KMAC, MenuWeek::Current)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(FetcherError::HttpStatus(404))
+        ));
+    }
+}
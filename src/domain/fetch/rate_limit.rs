@@ -0,0 +1,154 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::{Mutex, OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+
+use super::HttpConfig;
+
+/// The longest a request waits for the rate limit, even if the rate is close to zero
+const MAX_WAIT: Duration = Duration::from_secs(60 * 60);
+
+/// Caps the requests per second and the concurrent connections to the upstream host.
+///
+/// Requests over the limit wait for their turn in order. Clones share their limits.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<TokenBucket>>,
+    connections: Arc<Semaphore>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// Tokens refilled per second
+    rate: f64,
+    capacity: f64,
+    /// Negative while requests wait for tokens that are not refilled yet
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Allows a request to be sent. Holds one of the connections until it is dropped.
+#[derive(Debug)]
+pub struct RatePermit {
+    _connection: OwnedSemaphorePermit,
+}
+
+impl RateLimiter {
+    /// Allows `requests_per_sec` requests per second with bursts of up to `burst` requests, and at
+    /// most `max_connections` requests at the same time.
+    ///
+    /// Rates that are not positive make requests wait as long as possible.
+    pub fn new(requests_per_sec: f64, burst: u32, max_connections: usize) -> Self {
+        let capacity = f64::from(burst.max(1));
+        let rate = if requests_per_sec.is_nan() {
+            f64::MIN_POSITIVE
+        } else {
+            requests_per_sec.clamp(f64::MIN_POSITIVE, f64::MAX)
+        };
+
+        Self {
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                rate,
+                capacity,
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            })),
+            connections: Arc::new(Semaphore::new(max_connections.max(1))),
+        }
+    }
+
+    /// Waits until a request may be sent.
+    pub async fn acquire(&self) -> RatePermit {
+        let connection = match self.connections.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                log::info!("Upstream connection limit reached. Request is queued");
+                // unwrap: the semaphore is never closed
+                self.connections.clone().acquire_owned().await.unwrap()
+            }
+        };
+
+        let wait = self.bucket.lock().await.take(Instant::now());
+        if !wait.is_zero() {
+            log::info!("Upstream rate limit reached. Request is queued for {wait:?}");
+            tokio::time::sleep(wait).await;
+        }
+
+        RatePermit {
+            _connection: connection,
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        HttpConfig::default().rate_limiter()
+    }
+}
+
+impl TokenBucket {
+    /// Takes a token and returns how long to wait until it is refilled.
+    fn take(&mut self, now: Instant) -> Duration {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::try_from_secs_f64(-self.tokens / self.rate)
+                .map_or(MAX_WAIT, |wait| wait.min(MAX_WAIT))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::{RateLimiter, MAX_WAIT};
+
+    #[tokio::test(start_paused = true)]
+    async fn it_queues_requests_over_the_rate() {
+        let limiter = RateLimiter::new(2.0, 2, 10);
+        let start = Instant::now();
+
+        for _ in 0..6 {
+            drop(limiter.clone().acquire().await);
+        }
+
+        // Two requests of the burst, then one every half second
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_queues_requests_over_the_connection_limit() {
+        let limiter = RateLimiter::new(100.0, 10, 1);
+
+        let permit = limiter.acquire().await;
+        let queued = tokio::time::timeout(Duration::from_secs(1), limiter.acquire()).await;
+        assert!(queued.is_err());
+
+        drop(permit);
+        let queued = tokio::time::timeout(Duration::from_secs(1), limiter.acquire()).await;
+        assert!(queued.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_caps_the_wait_for_rates_close_to_zero() {
+        for rate in [0.0, -1.0, f64::NAN] {
+            let limiter = RateLimiter::new(rate, 1, 1);
+            let start = Instant::now();
+
+            drop(limiter.acquire().await);
+            drop(limiter.acquire().await);
+
+            assert_eq!(start.elapsed(), MAX_WAIT);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+
+/// Problems found while parsing a menu page that did not stop the rest of it from being parsed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    issues: Vec<ParseIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIssue {
+    /// A day section whose title contains no date
+    UndatedSection { title: String },
+    /// A table of a day section is missing
+    MissingTable {
+        date: NaiveDate,
+        class: &'static str,
+    },
+    /// A table row that could not be parsed
+    SkippedRow {
+        date: NaiveDate,
+        table: &'static str,
+        reason: String,
+    },
+    /// A class on a dish row that maps to no known label
+    UnknownLabelClass { date: NaiveDate, class: String },
+}
+
+impl ParseReport {
+    pub fn push(&mut self, issue: ParseIssue) {
+        if !self.issues.contains(&issue) {
+            self.issues.push(issue);
+        }
+    }
+
+    pub fn issues(&self) -> &[ParseIssue] {
+        &self.issues
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseIssue::UndatedSection { title } => {
+                write!(f, "section without a date: \"{title}\"")
+            }
+            ParseIssue::MissingTable { date, class } => {
+                write!(f, "{date}: no table with class \"{class}\"")
+            }
+            ParseIssue::SkippedRow {
+                date,
+                table,
+                reason,
+            } => write!(f, "{date}: skipped row of table \"{table}\": {reason}"),
+            ParseIssue::UnknownLabelClass { date, class } => {
+                write!(f, "{date}: unknown label class \"{class}\"")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in self.issues.iter() {
+            writeln!(f, "- {issue}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use serde::Serialize;
+
+use super::err::FetcherError;
+
+/// How often and how patiently transient fetch errors are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the delay before retry number `retry`, starting at 1.
+    ///
+    /// The delay is picked at random up to the exponential backoff so that concurrent requests do
+    /// not retry in lockstep.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let cap = self.base_delay.saturating_mul(factor).min(self.max_delay);
+
+        rand::rng().random_range(Duration::ZERO..=cap)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Stops requests to a host after repeated transient failures.
+///
+/// Once `failure_threshold` failures happened in a row the circuit of the host opens and requests
+/// fail immediately with [`FetcherError::CircuitOpen`]. After `open_dur` requests are let through
+/// again; the first failure reopens the circuit, the first success closes it. Clones share their
+/// state.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_dur: Duration,
+    hosts: Arc<Mutex<HashMap<String, HostState>>>,
+}
+
+#[derive(Debug, Default)]
+struct HostState {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_dur: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_dur,
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Fails if the circuit of `host` is open at `now`.
+    pub fn check(&self, host: &str, now: Instant) -> Result<(), FetcherError> {
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return Ok(());
+        };
+
+        match hosts.get_mut(host) {
+            Some(state) if state.open_until.is_some_and(|until| now < until) => {
+                Err(FetcherError::CircuitOpen {
+                    host: host.to_owned(),
+                })
+            }
+            Some(state) => {
+                state.open_until = None;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Records a successful request. Returns whether this closed the circuit of `host`.
+    pub fn record_success(&self, host: &str) -> bool {
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return false;
+        };
+
+        hosts
+            .remove(host)
+            .is_some_and(|state| state.failures >= self.failure_threshold)
+    }
+
+    /// Records a failed request. Returns whether this opened the circuit of `host`.
+    pub fn record_failure(&self, host: &str, now: Instant) -> bool {
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return false;
+        };
+
+        let state = hosts.entry(host.to_owned()).or_default();
+        state.failures += 1;
+
+        if state.failures >= self.failure_threshold {
+            state.open_until = Some(now + self.open_dur);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(60))
+    }
+}
+
+/// Counters of upstream requests, shared by all clones of a fetcher.
+#[derive(Debug, Default)]
+pub struct FetchMetrics {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+    circuit_opened: AtomicU64,
+    short_circuited: AtomicU64,
+}
+
+/// The counters of [`FetchMetrics`] at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FetchMetricsSnapshot {
+    pub requests: u64,
+    pub retries: u64,
+    pub failures: u64,
+    pub circuit_opened: u64,
+    pub short_circuited: u64,
+}
+
+impl FetchMetrics {
+    pub fn snapshot(&self) -> FetchMetricsSnapshot {
+        FetchMetricsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            circuit_opened: self.circuit_opened.load(Ordering::Relaxed),
+            short_circuited: self.short_circuited.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(super) fn inc_requests(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn inc_retries(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn inc_failures(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn inc_circuit_opened(&self) {
+        self.circuit_opened.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn inc_short_circuited(&self) {
+        self.short_circuited.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{CircuitBreaker, RetryPolicy};
+    use crate::domain::fetch::err::FetcherError;
+
+    #[test]
+    fn it_caps_the_jittered_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+
+        for _ in 0..100 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+            assert!(policy.backoff(2) <= Duration::from_millis(200));
+            assert!(policy.backoff(10) <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn it_opens_and_closes_the_circuit() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert!(!breaker.record_failure("host", now));
+        assert!(breaker.check("host", now).is_ok());
+        assert!(breaker.record_failure("host", now));
+        assert!(matches!(
+            breaker.check("host", now + Duration::from_secs(1)),
+            Err(FetcherError::CircuitOpen { .. })
+        ));
+        assert!(breaker.check("other", now).is_ok());
+
+        // Trial request after the circuit was open long enough
+        let later = now + Duration::from_secs(11);
+        assert!(breaker.check("host", later).is_ok());
+        assert!(breaker.record_failure("host", later));
+        assert!(breaker.check("host", later).is_err());
+
+        let much_later = later + Duration::from_secs(11);
+        assert!(breaker.check("host", much_later).is_ok());
+        assert!(breaker.record_success("host"));
+        assert!(!breaker.record_failure("host", much_later));
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::OnceCell;
+
+/// Coalesces concurrent calls for the same key into one.
+///
+/// While a call for a key is in flight, further calls for that key wait for it and get a clone of
+/// its result instead of running their own. Clones share the calls in flight.
+#[derive(Debug)]
+pub struct SingleFlight<K, V> {
+    in_flight: Arc<Mutex<HashMap<K, Arc<OnceCell<V>>>>>,
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Runs `f` unless a call for `key` is already in flight, and returns the result of the call.
+    ///
+    /// If the running call is cancelled, one of the waiting calls runs its own `f` instead.
+    pub async fn run<F, Fut>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = self
+            .in_flight
+            .lock()
+            .map(|mut in_flight| in_flight.entry(key.clone()).or_default().clone())
+            // A poisoned map only costs the coalescing
+            .unwrap_or_default();
+
+        let val = cell.get_or_init(f).await.clone();
+
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            if in_flight
+                .get(&key)
+                .is_some_and(|running| Arc::ptr_eq(running, &cell))
+            {
+                in_flight.remove(&key);
+            }
+        }
+
+        val
+    }
+}
+
+impl<K, V> Clone for SingleFlight<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use super::SingleFlight;
+
+    #[tokio::test]
+    async fn it_runs_concurrent_calls_once() {
+        let flights = SingleFlight::<&str, Result<u32, String>>::new();
+        let upstream_calls = Arc::new(AtomicUsize::new(0));
+
+        let mock_fetch = |calls: Arc<AtomicUsize>, res: Result<u32, String>| async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            res
+        };
+
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let flights = flights.clone();
+                let calls = upstream_calls.clone();
+                tokio::spawn(async move {
+                    flights
+                        .run("academica", || mock_fetch(calls, Err("503".to_owned())))
+                        .await
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), Err("503".to_owned()));
+        }
+        assert_eq!(upstream_calls.load(Ordering::SeqCst), 1);
+
+        // Finished calls are not reused
+        let res = flights
+            .run("academica", || mock_fetch(upstream_calls.clone(), Ok(1)))
+            .await;
+        assert_eq!(res, Ok(1));
+        assert_eq!(upstream_calls.load(Ordering::SeqCst), 2);
+    }
+}
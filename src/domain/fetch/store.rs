@@ -0,0 +1,298 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::model::{Canteen, Versioned, WeekMenu};
+
+use super::{MenuWeek, Validators};
+
+/// A cached week menu as it is persisted.
+///
+/// The menu keeps the time it was fetched at, so loaded entries are only as fresh as they were
+/// when they were stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredWeekMenu {
+    pub canteen: Canteen,
+    pub week: MenuWeek,
+    pub validators: Validators,
+    pub menu: WeekMenu,
+}
+
+impl StoredWeekMenu {
+    /// Returns the Monday of the week the menu is for.
+    pub fn monday(&self) -> NaiveDate {
+        let fetched_on = self.menu.fetched_at().with_timezone(&Local).date_naive();
+        self.week.monday(fetched_on)
+    }
+}
+
+/// Persists cached week menus so the cache survives restarts.
+pub trait MenuStore: Debug + Send + Sync {
+    /// Loads all stored menus.
+    fn load_all(&self) -> anyhow::Result<Vec<StoredWeekMenu>>;
+
+    /// Stores `menu`, replacing a stored menu of the same canteen and week.
+    ///
+    /// Blocks on the file system, so async code runs it with [`tokio::task::spawn_blocking`].
+    fn save(&self, menu: &StoredWeekMenu) -> anyhow::Result<()>;
+}
+
+/// Stores every week menu as a JSON snapshot in a directory.
+///
+/// Snapshots of another [schema version](crate::domain::model::SCHEMA_VERSION) are skipped on load.
+#[derive(Debug, Clone)]
+pub struct JsonDirStore {
+    dir: PathBuf,
+}
+
+impl JsonDirStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Names snapshots by the Monday of their week, as the next week of one Monday is the current
+    /// week of the next.
+    fn path(&self, canteen: Canteen, monday: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}-{monday}.json", canteen.slug()))
+    }
+
+    /// Removes the snapshots of `canteen` for weeks before `monday`.
+    fn remove_before(&self, canteen: Canteen, monday: NaiveDate) -> anyhow::Result<()> {
+        let prefix = format!("{}-", canteen.slug());
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let week_of_file = path
+                .file_name()
+                .and_then(|name| name.to_str()?.strip_prefix(&prefix)?.strip_suffix(".json"))
+                .and_then(|date| date.parse::<NaiveDate>().ok());
+
+            if week_of_file.is_some_and(|week_of_file| week_of_file < monday) {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load(path: &Path) -> anyhow::Result<StoredWeekMenu> {
+        let bytes = fs::read(path)?;
+        let menu: Versioned<StoredWeekMenu> = serde_json::from_slice(&bytes)?;
+        Ok(menu.into_data())
+    }
+}
+
+impl MenuStore for JsonDirStore {
+    fn load_all(&self) -> anyhow::Result<Vec<StoredWeekMenu>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        // Snapshots of older versions were named by their week page, so two of them can hold the
+        // same week. The newest one wins.
+        let mut menus = HashMap::<(Canteen, NaiveDate), StoredWeekMenu>::new();
+        let loaded = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                Self::load(&path)
+                    .inspect_err(|e| log::warn!("Can not load menu {}: {e}", path.display()))
+                    .ok()
+            });
+        for menu in loaded {
+            match menus.entry((menu.canteen, menu.monday())) {
+                Entry::Occupied(mut stored)
+                    if stored.get().menu.fetched_at() < menu.menu.fetched_at() =>
+                {
+                    stored.insert(menu);
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(vacant) => {
+                    vacant.insert(menu);
+                }
+            }
+        }
+
+        Ok(menus.into_values().collect())
+    }
+
+    fn save(&self, menu: &StoredWeekMenu) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Concurrent saves of the same menu each write their own file before replacing it
+        static SAVES: AtomicU64 = AtomicU64::new(0);
+        let monday = menu.monday();
+        let path = self.path(menu.canteen, monday);
+        let tmp_path = path.with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            SAVES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp_path, serde_json::to_vec(&Versioned::new(menu))?)?;
+        fs::rename(tmp_path, path)?;
+
+        let this_monday = MenuWeek::Current.monday(Local::now().date_naive());
+        self.remove_before(menu.canteen, monday.min(this_monday))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use scraper::Html;
+
+    use super::{JsonDirStore, MenuStore, StoredWeekMenu};
+    use crate::domain::{
+        fetch::{HtmlMenuFetcher, MenuWeek, Validators},
+        model::{Canteen, DayMenu, PriceTier},
+    };
+
+    #[test]
+    fn it_keeps_menus_and_their_timestamps() {
+        let dir = std::env::temp_dir().join(format!("fressbot-menus-{}", std::process::id()));
+        let store = JsonDirStore::new(dir.clone());
+
+        let html = Html::parse_document(include_str!("../../../fixtures/html/academica-w.html"));
+        let fetched_at = Utc.with_ymd_and_hms(2024, 10, 14, 9, 30, 0).unwrap();
+        let menu = HtmlMenuFetcher::new()
+            .parse_week(&html)
+            .0
+            .with_fetched_at(fetched_at);
+
+        store
+            .save(&StoredWeekMenu {
+                canteen: Canteen::Jülich,
+                week: MenuWeek::Current,
+                validators: Validators {
+                    etag: Some("\"v1\"".to_owned()),
+                    last_modified: None,
+                },
+                menu: menu.clone(),
+            })
+            .unwrap();
+
+        let loaded = store.load_all().unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        let [stored] = &loaded[..] else {
+            panic!("expected one stored menu, got {}", loaded.len());
+        };
+        assert_eq!(stored.canteen, Canteen::Jülich);
+        assert_eq!(stored.week, MenuWeek::Current);
+        assert_eq!(stored.validators.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(stored.menu.fetched_at(), fetched_at);
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 10, 14).unwrap();
+        let (Some(DayMenu::Open(before)), Some(DayMenu::Open(after))) =
+            (menu.day(date), stored.menu.day(date))
+        else {
+            panic!("expected a menu on monday");
+        };
+        assert_eq!(
+            before.fmt_html(PriceTier::Guest).unwrap(),
+            after.fmt_html(PriceTier::Guest).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_saves_the_same_menu_concurrently() {
+        let dir = std::env::temp_dir().join(format!("fressbot-saves-{}", std::process::id()));
+        let store = JsonDirStore::new(dir.clone());
+
+        let html = Html::parse_document(include_str!("../../../fixtures/html/academica-w.html"));
+        let stored = StoredWeekMenu {
+            canteen: Canteen::Vita,
+            week: MenuWeek::Next,
+            validators: Validators::default(),
+            menu: HtmlMenuFetcher::new().parse_week(&html).0,
+        };
+
+        std::thread::scope(|scope| {
+            let saves: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| store.save(&stored)))
+                .collect();
+            for save in saves {
+                save.join().unwrap().unwrap();
+            }
+        });
+
+        let loaded = store.load_all().unwrap();
+        let files = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(files, 1);
+    }
+
+    #[test]
+    fn it_keeps_the_newest_menu_of_a_week_across_the_rollover() {
+        let dir = std::env::temp_dir().join(format!("fressbot-rollover-{}", std::process::id()));
+        let store = JsonDirStore::new(dir.clone());
+
+        let html = Html::parse_document(include_str!("../../../fixtures/html/academica-w.html"));
+        let stored = |canteen, week, day, etag: &str| StoredWeekMenu {
+            canteen,
+            week,
+            validators: Validators {
+                etag: Some(etag.to_owned()),
+                last_modified: None,
+            },
+            menu: HtmlMenuFetcher::new()
+                .parse_week(&html)
+                .0
+                .with_fetched_at(Utc.with_ymd_and_hms(2024, 10, day, 12, 0, 0).unwrap()),
+        };
+
+        store
+            .save(&stored(Canteen::Vita, MenuWeek::Current, 7, "last week"))
+            .unwrap();
+        // Sunday's next week is Monday's current week
+        store
+            .save(&stored(Canteen::Vita, MenuWeek::Next, 13, "sunday"))
+            .unwrap();
+        store
+            .save(&stored(Canteen::Vita, MenuWeek::Current, 14, "monday"))
+            .unwrap();
+
+        // Older versions named the snapshots by their week page
+        store
+            .save(&stored(Canteen::Süd, MenuWeek::Current, 14, "monday"))
+            .unwrap();
+        store
+            .save(&stored(Canteen::Süd, MenuWeek::Next, 13, "sunday"))
+            .unwrap();
+        std::fs::rename(
+            dir.join(format!("{}-2024-10-14.json", Canteen::Süd.slug())),
+            dir.join("süd-next.json"),
+        )
+        .unwrap();
+        store
+            .save(&stored(Canteen::Süd, MenuWeek::Current, 14, "monday"))
+            .unwrap();
+
+        let loaded = store.load_all().unwrap();
+        let files = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        for canteen in [Canteen::Süd, Canteen::Vita] {
+            let menu = loaded.iter().find(|menu| menu.canteen == canteen).unwrap();
+            assert_eq!(menu.validators.etag.as_deref(), Some("monday"));
+            assert_eq!(
+                menu.monday(),
+                chrono::NaiveDate::from_ymd_opt(2024, 10, 14).unwrap()
+            );
+        }
+        // The snapshot of last week is gone, the one of the older version is left
+        assert_eq!(files, 3);
+    }
+}
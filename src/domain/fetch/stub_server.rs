@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Answers every request on a local port with the raw HTTP response `respond` picks for it and
+/// returns the URL of the server.
+///
+/// Connections stay open between requests. An empty response answers nothing, so the client times
+/// out.
+pub(crate) async fn serve<R: Into<String>>(
+    respond: impl Fn(&str) -> R + Send + Sync + 'static,
+) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let respond = Arc::new(respond);
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let respond = respond.clone();
+
+            tokio::spawn(async move {
+                let mut buf = [0; 4096];
+                while let Ok(len @ 1..) = stream.read(&mut buf).await {
+                    let response: String = respond(&String::from_utf8_lossy(&buf[..len])).into();
+                    if stream.write_all(response.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    format!("http://{addr}/")
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumIter, EnumString, IntoStaticStr};
+
+/// Allergens as marked on the menus of the Studierendenwerk Aachen.
+///
+/// Parses from and converts into the code used on the menu, e.g. "A1".
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Allergen {
+    #[strum(serialize = "A")]
+    Gluten,
+    #[strum(serialize = "A1")]
+    Wheat,
+    #[strum(serialize = "A2")]
+    Rye,
+    #[strum(serialize = "A3")]
+    Barley,
+    #[strum(serialize = "A4")]
+    Oats,
+    #[strum(serialize = "A5")]
+    Spelt,
+    #[strum(serialize = "B")]
+    Celery,
+    #[strum(serialize = "C")]
+    Crustaceans,
+    #[strum(serialize = "D")]
+    Eggs,
+    #[strum(serialize = "E")]
+    Fish,
+    #[strum(serialize = "F")]
+    Peanuts,
+    #[strum(serialize = "G")]
+    Soy,
+    #[strum(serialize = "H")]
+    Milk,
+    #[strum(serialize = "I")]
+    Nuts,
+    #[strum(serialize = "J")]
+    Mustard,
+    #[strum(serialize = "K")]
+    Sesame,
+    #[strum(serialize = "L")]
+    Sulphites,
+    #[strum(serialize = "M")]
+    Lupin,
+    #[strum(serialize = "N")]
+    Molluscs,
+}
+
+impl Allergen {
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Formats the German name of the allergen as given in the menu legend.
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Allergen::Gluten => "Glutenhaltiges Getreide",
+            Allergen::Wheat => "Weizen",
+            Allergen::Rye => "Roggen",
+            Allergen::Barley => "Gerste",
+            Allergen::Oats => "Hafer",
+            Allergen::Spelt => "Dinkel",
+            Allergen::Celery => "Sellerie",
+            Allergen::Crustaceans => "Krebstiere",
+            Allergen::Eggs => "Eier",
+            Allergen::Fish => "Fische",
+            Allergen::Peanuts => "Erdnüsse",
+            Allergen::Soy => "Sojabohnen",
+            Allergen::Milk => "Milch",
+            Allergen::Nuts => "Schalenfrüchte",
+            Allergen::Mustard => "Senf",
+            Allergen::Sesame => "Sesamsamen",
+            Allergen::Sulphites => "Schwefeldioxid und Sulfite",
+            Allergen::Lupin => "Lupinen",
+            Allergen::Molluscs => "Weichtiere",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Additives as marked on the menus of the Studierendenwerk Aachen.
+///
+/// Parses from and converts into the code used on the menu, e.g. "2".
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Additive {
+    #[strum(serialize = "1")]
+    Colouring,
+    #[strum(serialize = "2")]
+    Preservative,
+    #[strum(serialize = "3")]
+    Antioxidant,
+    #[strum(serialize = "4")]
+    FlavourEnhancer,
+    #[strum(serialize = "5")]
+    Sulphurated,
+    #[strum(serialize = "6")]
+    Blackened,
+    #[strum(serialize = "7")]
+    Waxed,
+    #[strum(serialize = "8")]
+    Phosphate,
+    #[strum(serialize = "9")]
+    Sweetener,
+    #[strum(serialize = "10")]
+    Phenylalanine,
+}
+
+impl Additive {
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Formats the German description of the additive as given in the menu legend.
+impl fmt::Display for Additive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Additive::Colouring => "mit Farbstoff",
+            Additive::Preservative => "mit Konservierungsstoff",
+            Additive::Antioxidant => "mit Antioxidationsmittel",
+            Additive::FlavourEnhancer => "mit Geschmacksverstärker",
+            Additive::Sulphurated => "geschwefelt",
+            Additive::Blackened => "geschwärzt",
+            Additive::Waxed => "gewachst",
+            Additive::Phosphate => "mit Phosphat",
+            Additive::Sweetener => "mit Süßungsmitteln",
+            Additive::Phenylalanine => "enthält eine Phenylalaninquelle",
+        };
+
+        write!(f, "{name}")
+    }
+}
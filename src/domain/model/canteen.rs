@@ -1,20 +1,45 @@
+use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, Display, EnumCount, EnumIter};
 
-#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter, AsRefStr, EnumCount, Hash)]
+/// Serializes as the name the canteen has in URLs, see [`Canteen::slug`].
+#[allow(clippy::upper_case_acronyms)]
+#[derive(
+    Debug,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    AsRefStr,
+    EnumCount,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
 pub enum Canteen {
     #[strum(serialize = "Academica")]
+    #[serde(rename = "academica")]
     Academica,
     #[strum(serialize = "Ahornstraße")]
+    #[serde(rename = "ahornstrasse")]
     Ahorn,
+    #[serde(rename = "bayernallee")]
     Bayernallee,
     #[strum(serialize = "Bistro Templergraben")]
+    #[serde(rename = "templergraben")]
     Bistro,
     #[strum(serialize = "Eupener Straße")]
+    #[serde(rename = "eupenerstrasse")]
     Eupener,
+    #[serde(rename = "juelich")]
     Jülich,
+    #[serde(rename = "kmac")]
     KMAC,
     #[strum(serialize = "Südpark")]
+    #[serde(rename = "suedpark")]
     Süd,
+    #[serde(rename = "vita")]
     Vita,
 }
 
@@ -22,6 +47,21 @@ impl Canteen {
     pub fn parser() -> parser::CanteenParser {
         parser::CanteenParser
     }
+
+    /// Returns the name of the canteen in URLs, e.g. "ahornstrasse".
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Canteen::Academica => "academica",
+            Canteen::Ahorn => "ahornstrasse",
+            Canteen::Bayernallee => "bayernallee",
+            Canteen::Bistro => "templergraben",
+            Canteen::Eupener => "eupenerstrasse",
+            Canteen::Jülich => "juelich",
+            Canteen::KMAC => "kmac",
+            Canteen::Süd => "suedpark",
+            Canteen::Vita => "vita",
+        }
+    }
 }
 
 pub(super) mod parser {
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, IntoStaticStr};
+
+/// Climate information of one portion of a dish.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Climate {
+    /// CO₂ equivalent in grams
+    pub co2_grams: Option<u32>,
+    pub labels: Vec<ClimateLabel>,
+}
+
+impl Climate {
+    pub fn is_empty(&self) -> bool {
+        self.co2_grams.is_none() && self.labels.is_empty()
+    }
+}
+
+/// Sustainability badges some canteens put on their dishes.
+#[derive(
+    Debug,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ClimateLabel {
+    /// "Klimateller", a dish with a particularly small footprint
+    #[strum(serialize = "🌱")]
+    ClimatePlate,
+}
@@ -1,7 +1,9 @@
 use chrono::Datelike;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DayOfWeek {
     Today,
     Tomorrow,
@@ -1,60 +1,165 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use itertools::Itertools;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Write},
 };
 
-use strum_macros::{Display, EnumIter, IntoStaticStr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
-#[derive(Debug, Clone)]
+pub use super::allergen::{Additive, Allergen};
+pub use super::climate::{Climate, ClimateLabel};
+pub use super::nutrition::Nutrition;
+pub use super::price::{Price, PriceTier};
+
+/// All day menus published on a canteen's week page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekMenu {
+    days: BTreeMap<NaiveDate, DayMenu>,
+    fetched_at: DateTime<Utc>,
+    #[serde(skip)]
+    stale: bool,
+    /// Days that are closed only because their menu was not found on the page
+    #[serde(skip)]
+    missing_menus: BTreeSet<NaiveDate>,
+}
+
+impl WeekMenu {
+    pub fn new(days: BTreeMap<NaiveDate, DayMenu>) -> Self {
+        Self {
+            days,
+            fetched_at: Utc::now(),
+            stale: false,
+            missing_menus: BTreeSet::new(),
+        }
+    }
+
+    /// Marks the closed days in `missing_menus` as closed for lack of a menu on the page.
+    pub fn with_missing_menus(mut self, missing_menus: BTreeSet<NaiveDate>) -> Self {
+        self.missing_menus = missing_menus;
+        self
+    }
+
+    pub fn with_fetched_at(mut self, fetched_at: DateTime<Utc>) -> Self {
+        self.fetched_at = fetched_at;
+        self
+    }
+
+    /// Marks the menu as served from data that is no longer fresh.
+    pub fn into_stale(mut self) -> Self {
+        self.stale = true;
+        self
+    }
+
+    pub fn fetched_at(&self) -> DateTime<Utc> {
+        self.fetched_at
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Returns whether `date` is closed only because its menu was not found on the page.
+    pub fn is_menu_missing(&self, date: NaiveDate) -> bool {
+        self.missing_menus.contains(&date)
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Option<&DayMenu> {
+        self.days.get(&date)
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = (&NaiveDate, &DayMenu)> {
+        self.days.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DayMenu {
+    Open(Menu),
+    Closed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Menu {
-    dishes: HashMap<String, Vec<Dish>>,
+    dishes: BTreeMap<Category, Vec<Dish>>,
     extras: Vec<MenuExtra>,
+    /// When the menu was fetched, if it is served from stale data
+    #[serde(skip)]
+    stale_since: Option<DateTime<Utc>>,
 }
 
 impl Menu {
-    pub fn new<E: Into<MenuExtra>>(dishes: HashMap<String, Vec<Dish>>, extras: Vec<E>) -> Self {
+    pub fn new<E: Into<MenuExtra>>(dishes: BTreeMap<Category, Vec<Dish>>, extras: Vec<E>) -> Self {
         Self {
             dishes,
             extras: extras.into_iter().map(Into::<MenuExtra>::into).collect(),
+            stale_since: None,
         }
     }
 
-    pub fn fmt_html(&self) -> Result<String, fmt::Error> {
-        let mut s = String::new();
-        for (n, (categ, dishes)) in self
-            .dishes
+    pub fn with_stale_since(mut self, stale_since: Option<DateTime<Utc>>) -> Self {
+        self.stale_since = stale_since;
+        self
+    }
+
+    pub fn stale_since(&self) -> Option<DateTime<Utc>> {
+        self.stale_since
+    }
+
+    /// Formats a note on how old the menu is as of `now`, if it is served from stale data.
+    pub fn fmt_stale_note_html(&self, now: DateTime<Utc>) -> Option<String> {
+        let minutes = (now - self.stale_since?).num_minutes().max(1);
+
+        let age = if minutes < 60 {
+            format!("{minutes} Min.")
+        } else {
+            format!("{} Std.", minutes / 60)
+        };
+
+        Some(format!("<i>⏱ Stand von vor {age}</i>"))
+    }
+
+    pub fn dishes(&self) -> impl Iterator<Item = &Dish> {
+        self.dishes.values().flatten()
+    }
+
+    /// Returns the dishes grouped by category, in display order.
+    pub fn categories(&self) -> impl Iterator<Item = (&Category, &[Dish])> {
+        self.dishes
             .iter()
-            .sorted_by(|(k1, _), (k2, _)| k1.cmp(k2))
-            .enumerate()
-        {
+            .map(|(categ, dishes)| (categ, dishes.as_slice()))
+    }
+
+    pub fn extras(&self) -> &[MenuExtra] {
+        &self.extras
+    }
+
+    pub fn fmt_html(&self, tier: PriceTier) -> Result<String, fmt::Error> {
+        let mut s = String::new();
+        for (n, (categ, dishes)) in self.dishes.iter().enumerate() {
             if dishes.is_empty() {
                 continue;
             }
 
-            let emoji = match categ.as_str() {
-                "Klassiker" => "🍴",
-                "Vegetarisch" => "🥦",
-                "Tellergericht" => "🍲",
-                "Burger" => "🍔",
-                "Wok" => "🥡",
-                "Pizza" => "🍕",
-                _ => "",
-            };
-
             write!(s, "<em>{categ}</em>")?;
-            if !emoji.is_empty() {
+            if let Some(emoji) = categ.emoji() {
                 write!(s, " {emoji}")?;
             }
-            write!(s, "\n")?;
+            writeln!(s)?;
 
             for dish in dishes {
-                let dish_md = dish.fmt_html()?;
-                write!(s, "{dish_md}\n")?;
+                let dish_md = dish.fmt_html(tier)?;
+                writeln!(s, "{dish_md}")?;
             }
 
             if n + 1 < self.dishes.len() {
-                write!(s, "\n")?;
+                writeln!(s)?;
             }
         }
 
@@ -65,51 +170,274 @@ impl Menu {
 
         Ok(s)
     }
+
+    /// Returns all dishes ordered by their CO₂ footprint, smallest first.
+    ///
+    /// Dishes without a footprint come last.
+    pub fn dishes_by_footprint(&self) -> Vec<&Dish> {
+        self.dishes()
+            .sorted_by_key(|dish| (dish.co2_grams().is_none(), dish.co2_grams()))
+            .collect()
+    }
+
+    /// Formats all dishes ordered by their CO₂ footprint.
+    pub fn fmt_climate_html(&self) -> Result<String, fmt::Error> {
+        let mut s = String::new();
+        for dish in self.dishes_by_footprint() {
+            writeln!(s, "{}", dish.fmt_climate_html()?)?;
+        }
+
+        Ok(s.trim_end().to_owned())
+    }
+
+    /// Formats the nutrition facts, allergens and additives of every dish.
+    pub fn fmt_details_html(&self) -> Result<String, fmt::Error> {
+        let mut s = String::new();
+        for (categ, dishes) in self.dishes.iter() {
+            for dish in dishes {
+                let dish_html = dish.fmt_details_html()?;
+                writeln!(s, "<em>{categ}</em>: {dish_html}")?;
+            }
+        }
+
+        Ok(s.trim_end().to_owned())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MenuExtra {
     category: String,
-    extra: String,
+    options: Vec<SideDish>,
 }
 
 impl MenuExtra {
-    pub fn new(category: String, extra: String) -> Self {
-        Self { category, extra }
+    pub fn new(category: String, options: Vec<SideDish>) -> Self {
+        Self { category, options }
     }
 
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    pub fn options(&self) -> &[SideDish] {
+        &self.options
+    }
+
+    /// Formats the extras as a sentence, e.g. "<em>Hauptbeilagen</em>: Pommes, Reis oder Nudeln".
     pub fn fmt_html(&self) -> Result<String, std::fmt::Error> {
+        let names: Vec<_> = self.options.iter().map(|option| option.name()).collect();
+
+        let options = match names.split_last() {
+            None => String::new(),
+            Some((last, [])) => last.to_string(),
+            Some((last, init)) => init.join(", ") + " oder " + last,
+        };
+
         let mut s = String::new();
-        write!(s, "<em>{}</em>: {}", self.category, self.extra)?;
+        write!(s, "<em>{}</em>: {}", self.category, options)?;
         Ok(s)
     }
 }
 
-impl<S: Into<String>> From<(S, S)> for MenuExtra {
-    fn from(value: (S, S)) -> Self {
-        Self::new(value.0.into(), value.1.into())
+impl<S: Into<String>> From<(S, Vec<SideDish>)> for MenuExtra {
+    fn from(value: (S, Vec<SideDish>)) -> Self {
+        Self::new(value.0.into(), value.1)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// One of the side dishes to choose from in a [`MenuExtra`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SideDish {
+    name: String,
+    labels: Vec<Label>,
+    allergens: Vec<Allergen>,
+    additives: Vec<Additive>,
+}
+
+impl SideDish {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            labels: vec![],
+            allergens: vec![],
+            additives: vec![],
+        }
+    }
+
+    pub fn with_labels(mut self, labels: Vec<Label>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Adds the allergens and additives of a marker to the side dish.
+    pub fn add_markers(&mut self, allergens: Vec<Allergen>, additives: Vec<Additive>) {
+        self.allergens.extend(allergens);
+        self.allergens.sort();
+        self.allergens.dedup();
+
+        self.additives.extend(additives);
+        self.additives.sort();
+        self.additives.dedup();
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn allergens(&self) -> &[Allergen] {
+        &self.allergens
+    }
+
+    pub fn additives(&self) -> &[Additive] {
+        &self.additives
+    }
+
+    pub fn contains_allergen(&self, allergen: Allergen) -> bool {
+        self.allergens.contains(&allergen)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Dish {
     name: String,
+    #[serde(rename = "ingredients")]
     ingreds: Vec<String>,
     labels: Vec<Label>,
-    price: Option<String>,
+    price: Option<Price>,
+    allergens: Vec<Allergen>,
+    additives: Vec<Additive>,
+    nutrition: Option<Nutrition>,
+    climate: Option<Climate>,
 }
 
 impl Dish {
-    pub fn new(name: String, descs: Vec<String>, labels: Vec<Label>, price: Option<String>) -> Self {
+    pub fn new(name: String, descs: Vec<String>, labels: Vec<Label>, price: Option<Price>) -> Self {
         Self {
             name,
             ingreds: descs,
             labels,
             price,
+            allergens: vec![],
+            additives: vec![],
+            nutrition: None,
+            climate: None,
         }
     }
 
-    pub fn fmt_html(&self) -> Result<String, fmt::Error> {
+    /// Sets the allergens and additives marked on any part of the dish.
+    pub fn with_markers(mut self, allergens: Vec<Allergen>, additives: Vec<Additive>) -> Self {
+        self.allergens = allergens;
+        self.additives = additives;
+        self
+    }
+
+    pub fn with_nutrition(mut self, nutrition: Option<Nutrition>) -> Self {
+        self.nutrition = nutrition;
+        self
+    }
+
+    pub fn with_climate(mut self, climate: Option<Climate>) -> Self {
+        self.climate = climate;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ingreds(&self) -> &[String] {
+        &self.ingreds
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn price(&self) -> Option<&Price> {
+        self.price.as_ref()
+    }
+
+    pub fn allergens(&self) -> &[Allergen] {
+        &self.allergens
+    }
+
+    pub fn additives(&self) -> &[Additive] {
+        &self.additives
+    }
+
+    pub fn contains_allergen(&self, allergen: Allergen) -> bool {
+        self.allergens.contains(&allergen)
+    }
+
+    pub fn nutrition(&self) -> Option<&Nutrition> {
+        self.nutrition.as_ref()
+    }
+
+    pub fn climate(&self) -> Option<&Climate> {
+        self.climate.as_ref()
+    }
+
+    /// CO₂ equivalent of the dish in grams, if the menu gives one.
+    pub fn co2_grams(&self) -> Option<u32> {
+        self.climate.as_ref().and_then(|climate| climate.co2_grams)
+    }
+
+    /// Formats the dish with its CO₂ footprint, e.g. "<strong>Linsen-Curry</strong> – 540 g CO₂".
+    pub fn fmt_climate_html(&self) -> Result<String, fmt::Error> {
+        let mut html = String::new();
+        write!(html, "<strong>{}</strong>", self.name)?;
+
+        if let Some(ref climate) = self.climate {
+            for label in climate.labels.iter() {
+                write!(html, " {label}")?;
+            }
+        }
+
+        match self.co2_grams() {
+            Some(grams) => write!(html, " – {grams} g CO₂")?,
+            None => write!(html, " – <i>keine Angabe</i>")?,
+        }
+
+        Ok(html)
+    }
+
+    /// Formats the dish with its nutrition facts, allergens and additives.
+    pub fn fmt_details_html(&self) -> Result<String, fmt::Error> {
+        let mut html = String::new();
+        writeln!(html, "<strong>{}</strong>", self.name)?;
+
+        match self.nutrition {
+            Some(ref nutrition) => writeln!(html, "{}", nutrition.fmt_html()?)?,
+            None => writeln!(html, "<i>Keine Nährwertangaben</i>")?,
+        }
+
+        if !self.allergens.is_empty() {
+            writeln!(
+                html,
+                "Allergene: {}",
+                self.allergens.iter().map(|a| a.to_string()).join(", ")
+            )?;
+        }
+
+        if !self.additives.is_empty() {
+            writeln!(
+                html,
+                "Zusatzstoffe: {}",
+                self.additives.iter().map(|a| a.to_string()).join(", ")
+            )?;
+        }
+
+        Ok(html)
+    }
+
+    /// Formats the dish with its price for `tier`.
+    ///
+    /// Falls back to the student price if the menu lists none for `tier`.
+    pub fn fmt_html(&self, tier: PriceTier) -> Result<String, fmt::Error> {
         let mut html = String::new();
         write!(html, "<strong>{}</strong>", self.name)?;
         if !self.ingreds.is_empty() {
@@ -118,20 +446,42 @@ impl Dish {
 
         write!(html, "{}", self.ingreds.join(", "))?;
 
-        if !self.labels.is_empty() {
-            let label_emoj: Vec<_> = self.labels.iter().map(|l| format!("{l}")).collect();
+        let climate_labels = self
+            .climate
+            .iter()
+            .flat_map(|climate| climate.labels.iter());
+        let label_emoj: Vec<_> = self
+            .labels
+            .iter()
+            .map(|l| format!("{l}"))
+            .chain(climate_labels.map(|l| format!("{l}")))
+            .collect();
+        if !label_emoj.is_empty() {
             write!(html, " {}", label_emoj.join(" "))?;
         }
 
         if let Some(ref price) = self.price {
-            write!(html, " – <strong>{}</strong>", price)?;
+            match price.fmt_tier(tier) {
+                Some(price) => write!(html, " – <strong>{}</strong>", price)?,
+                None => write!(
+                    html,
+                    " – <strong>{}</strong> ({})",
+                    price,
+                    PriceTier::Student
+                )?,
+            }
         }
 
         Ok(html)
     }
 }
 
-#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter, IntoStaticStr)]
+/// The category of a dish as given on the menu.
+///
+/// Categories are displayed in declaration order. Unknown categories are kept
+/// verbatim in [`Category::Other`] and come last.
+#[derive(Debug, Display, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString)]
+#[strum(ascii_case_insensitive)]
 pub enum Category {
     #[strum(serialize = "Burger Classics")]
     BurgerClassic,
@@ -149,9 +499,43 @@ pub enum Category {
     #[strum(serialize = "Vegetarisch")]
     Veggie,
     Wok,
+    #[strum(default)]
+    Other(String),
 }
 
-#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter, IntoStaticStr)]
+/// Categories are stored by the name they have on the menu, so they can be map keys.
+impl Serialize for Category {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        // unwrap: unknown categories fall back to Category::Other
+        Ok(name.parse().unwrap())
+    }
+}
+
+impl Category {
+    pub fn emoji(&self) -> Option<&'static str> {
+        match self {
+            Category::BurgerClassic | Category::BurgerWeekly => Some("🍔"),
+            Category::Classic => Some("🍴"),
+            Category::PizzaClassic | Category::PizzaDaily => Some("🍕"),
+            Category::PlateDish => Some("🍲"),
+            Category::Veggie => Some("🥦"),
+            Category::Wok => Some("🥡"),
+            Category::Pasta | Category::Other(_) => None,
+        }
+    }
+}
+
+#[derive(
+    Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter, IntoStaticStr, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
 pub enum Label {
     #[strum(serialize = "🐮")]
     Beef,
@@ -166,3 +550,17 @@ pub enum Label {
     #[strum(serialize = "🥦")]
     Veggie,
 }
+
+impl Label {
+    /// Returns the German name of the label, e.g. "Geflügel".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Label::Beef => "Rind",
+            Label::Chicken => "Geflügel",
+            Label::Fish => "Fisch",
+            Label::Pork => "Schwein",
+            Label::Vegan => "vegan",
+            Label::Veggie => "vegetarisch",
+        }
+    }
+}
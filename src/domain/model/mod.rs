@@ -1,12 +1,24 @@
+mod allergen;
 mod canteen;
+mod climate;
 mod day_of_week;
 pub mod menu;
+mod nutrition;
+mod price;
+mod versioned;
 
+pub use allergen::{Additive, Allergen};
 pub use canteen::Canteen;
+pub use climate::{Climate, ClimateLabel};
 pub use day_of_week::DayOfWeek;
-pub use menu::Menu;
+pub use menu::{DayMenu, Menu, WeekMenu};
+pub use nutrition::Nutrition;
+pub use price::PriceTier;
+pub use versioned::{Versioned, SCHEMA_VERSION};
 
+#[allow(unused_imports)]
 pub mod parse {
     pub use super::canteen::parser::{parse as parse_canteen, CanteenParser};
     pub use super::day_of_week::parser::{parse_day_of_week, DayOfWeekParser};
+    pub use super::price::parser::{parse as parse_price_tier, PriceTierParser};
 }
@@ -0,0 +1,52 @@
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+/// Energy and macronutrients of one portion of a dish.
+///
+/// Masses are stored in milligrams to keep them exact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Nutrition {
+    pub energy_kj: Option<u32>,
+    pub energy_kcal: Option<u32>,
+    pub fat_mg: Option<u32>,
+    pub carbs_mg: Option<u32>,
+    pub protein_mg: Option<u32>,
+}
+
+impl Nutrition {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Formats the nutrition facts in one line, e.g. "671 kcal · Fett 22,2 g · Eiweiß 42,3 g".
+    pub fn fmt_html(&self) -> Result<String, fmt::Error> {
+        fn fmt_grams(mg: u32) -> String {
+            // round to one decimal place
+            let dg = (mg + 50) / 100;
+            format!("{},{} g", dg / 10, dg % 10)
+        }
+
+        let mut parts = vec![];
+
+        match (self.energy_kcal, self.energy_kj) {
+            (Some(kcal), _) => parts.push(format!("{kcal} kcal")),
+            (None, Some(kj)) => parts.push(format!("{kj} kJ")),
+            (None, None) => {}
+        }
+
+        for (name, mg) in [
+            ("Fett", self.fat_mg),
+            ("Kohlenhydrate", self.carbs_mg),
+            ("Eiweiß", self.protein_mg),
+        ] {
+            if let Some(mg) = mg {
+                parts.push(format!("{name} {}", fmt_grams(mg)));
+            }
+        }
+
+        let mut s = String::new();
+        write!(s, "{}", parts.join(" · "))?;
+        Ok(s)
+    }
+}
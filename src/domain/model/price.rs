@@ -0,0 +1,157 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, IntoStaticStr};
+
+/// The groups of people the canteens charge different prices.
+#[derive(
+    Debug,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumIter,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceTier {
+    #[default]
+    #[strum(serialize = "Studierende")]
+    Student,
+    #[strum(serialize = "Bedienstete")]
+    Employee,
+    #[strum(serialize = "Gäste")]
+    Guest,
+}
+
+/// The price of a dish in cents for every tier given on the menu.
+///
+/// The student price is always given, the others only on some pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Price {
+    #[serde(rename = "student_cents")]
+    student: u32,
+    #[serde(rename = "employee_cents")]
+    employee: Option<u32>,
+    #[serde(rename = "guest_cents")]
+    guest: Option<u32>,
+}
+
+impl Price {
+    pub fn new(student: u32, employee: Option<u32>, guest: Option<u32>) -> Self {
+        Self {
+            student,
+            employee,
+            guest,
+        }
+    }
+
+    /// Returns the price in cents for `tier`, if the menu lists one.
+    pub fn get(&self, tier: PriceTier) -> Option<u32> {
+        match tier {
+            PriceTier::Student => Some(self.student),
+            PriceTier::Employee => self.employee,
+            PriceTier::Guest => self.guest,
+        }
+    }
+
+    /// Formats the price for `tier` in German notation, e.g. "2,20 €".
+    pub fn fmt_tier(&self, tier: PriceTier) -> Option<String> {
+        self.get(tier)
+            .map(|cents| format!("{},{:02} €", cents / 100, cents % 100))
+    }
+}
+
+/// Formats the student price.
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // unwrap: the student price is always present
+        write!(f, "{}", self.fmt_tier(PriceTier::Student).unwrap())
+    }
+}
+
+impl PriceTier {
+    pub fn parser() -> parser::PriceTierParser {
+        parser::PriceTierParser
+    }
+}
+
+pub(super) mod parser {
+    use nom::{
+        branch::alt,
+        bytes::complete::{tag, tag_no_case},
+        combinator::{eof, opt, recognize},
+        sequence::{terminated, tuple},
+        IResult,
+    };
+
+    use super::PriceTier;
+
+    type ParseResult<'a> = IResult<&'a str, PriceTier>;
+
+    pub struct PriceTierParser;
+
+    impl PriceTierParser {
+        pub fn parse<'a>(&self, input: &'a str) -> ParseResult<'a> {
+            parse(input)
+        }
+    }
+
+    pub fn parse(input: &str) -> ParseResult<'_> {
+        terminated(alt((student, employee, guest)), eof)(input)
+    }
+
+    fn student(input: &str) -> ParseResult<'_> {
+        let (input, _) = alt((
+            tag_no_case("studierende"),
+            recognize(tuple((tag_no_case("student"), opt(tag_no_case("en"))))),
+            tag_no_case("studi"),
+        ))(input)?;
+
+        Ok((input, PriceTier::Student))
+    }
+
+    fn employee(input: &str) -> ParseResult<'_> {
+        let (input, _) = alt((
+            tag_no_case("bedienstete"),
+            tag_no_case("mitarbeitende"),
+            tag_no_case("mitarbeiter"),
+            tag_no_case("personal"),
+        ))(input)?;
+
+        Ok((input, PriceTier::Employee))
+    }
+
+    fn guest(input: &str) -> ParseResult<'_> {
+        let (input, _) = alt((
+            recognize(tuple((
+                tag_no_case("g"),
+                alt((tag("ä"), tag("Ä"), tag_no_case("ae"))),
+                tag_no_case("ste"),
+            ))),
+            tag_no_case("gast"),
+        ))(input)?;
+
+        Ok((input, PriceTier::Guest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PriceTier;
+
+    #[test]
+    fn it_parses_price_tiers() {
+        let parse = |input| PriceTier::parser().parse(input).map(|(_, tier)| tier);
+
+        assert_eq!(parse("mitarbeiter"), Ok(PriceTier::Employee));
+        assert_eq!(parse("Studierende"), Ok(PriceTier::Student));
+        assert_eq!(parse("gaeste"), Ok(PriceTier::Guest));
+        assert_eq!(parse("Gäste"), Ok(PriceTier::Guest));
+        assert!(parse("mitarbeiterin und so").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// Version of the JSON representation of the model, raised on every incompatible change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A model value together with the schema version it is represented in, e.g.
+/// `{"schema_version": 1, "data": …}`.
+///
+/// Deserializing fails for any other version than [`SCHEMA_VERSION`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Versioned<T> {
+    schema_version: u32,
+    data: T,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Versioned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw<T> {
+            schema_version: u32,
+            data: T,
+        }
+
+        let raw = Raw::<T>::deserialize(deserializer)?;
+        if raw.schema_version != SCHEMA_VERSION {
+            return Err(de::Error::custom(format!(
+                "unsupported schema version {}, expected {SCHEMA_VERSION}",
+                raw.schema_version
+            )));
+        }
+
+        Ok(Self::new(raw.data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, fmt::Debug};
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use serde::{de::DeserializeOwned, Serialize};
+    use strum::IntoEnumIterator;
+
+    use crate::domain::model::{
+        menu::{Category, Dish, Label, MenuExtra, Price, SideDish},
+        Additive, Allergen, Canteen, Climate, ClimateLabel, DayMenu, DayOfWeek, Menu, Nutrition,
+        PriceTier, WeekMenu,
+    };
+
+    use super::{Versioned, SCHEMA_VERSION};
+
+    const WEEK_MENU_V1: &str = include_str!("../../../fixtures/json/week-menu-v1.json");
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(val: T) {
+        let json = serde_json::to_string(&Versioned::new(&val)).unwrap();
+        let parsed: Versioned<T> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.into_data(), val, "{json}");
+    }
+
+    fn sample_dish() -> Dish {
+        Dish::new(
+            "Linsen-Curry".to_owned(),
+            vec!["Basmatireis".to_owned()],
+            vec![Label::Vegan],
+            Some(Price::new(220, Some(390), None)),
+        )
+        .with_markers(vec![Allergen::Celery], vec![Additive::Colouring])
+        .with_nutrition(Some(Nutrition {
+            energy_kj: Some(2808),
+            energy_kcal: Some(671),
+            fat_mg: Some(22_200),
+            carbs_mg: None,
+            protein_mg: Some(42_300),
+        }))
+        .with_climate(Some(Climate {
+            co2_grams: Some(540),
+            labels: vec![ClimateLabel::ClimatePlate],
+        }))
+    }
+
+    fn sample_extra() -> MenuExtra {
+        let mut rice = SideDish::new("Reis".to_owned()).with_labels(vec![Label::Vegan]);
+        rice.add_markers(vec![], vec![Additive::Preservative]);
+
+        MenuExtra::new(
+            "Hauptbeilagen".to_owned(),
+            vec![SideDish::new("Pommes".to_owned()), rice],
+        )
+    }
+
+    fn sample_week() -> WeekMenu {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let menu = Menu::new(
+            BTreeMap::from([
+                (Category::Veggie, vec![sample_dish()]),
+                (
+                    Category::Other("Suppe".to_owned()),
+                    vec![Dish::new("Tomatensuppe".to_owned(), vec![], vec![], None)],
+                ),
+            ]),
+            vec![sample_extra()],
+        );
+
+        WeekMenu::new(BTreeMap::from([
+            (date(14), DayMenu::Open(menu)),
+            (date(15), DayMenu::Closed),
+        ]))
+        .with_fetched_at(Utc.with_ymd_and_hms(2024, 10, 14, 9, 30, 0).unwrap())
+    }
+
+    #[test]
+    fn it_round_trips_every_model_type() {
+        Allergen::iter().for_each(round_trip);
+        Additive::iter().for_each(round_trip);
+        Canteen::iter().for_each(round_trip);
+        ClimateLabel::iter().for_each(round_trip);
+        DayOfWeek::iter().for_each(round_trip);
+        Label::iter().for_each(round_trip);
+        PriceTier::iter().for_each(round_trip);
+        [
+            Category::BurgerClassic,
+            Category::BurgerWeekly,
+            Category::Classic,
+            Category::Pasta,
+            Category::PizzaClassic,
+            Category::PizzaDaily,
+            Category::PlateDish,
+            Category::Veggie,
+            Category::Wok,
+            Category::Other("Suppe".to_owned()),
+        ]
+        .into_iter()
+        .for_each(round_trip);
+
+        round_trip(Price::new(220, None, Some(460)));
+        round_trip(sample_dish().nutrition().copied());
+        round_trip(sample_dish().climate().cloned());
+        round_trip(sample_extra().options()[1].clone());
+        round_trip(sample_extra());
+        round_trip(sample_dish());
+        round_trip(DayMenu::Closed);
+        round_trip(sample_week());
+    }
+
+    #[test]
+    fn it_keeps_the_json_format_of_version_1() {
+        let expected: serde_json::Value = serde_json::from_str(WEEK_MENU_V1).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(sample_week())).unwrap(),
+            expected
+        );
+
+        let parsed: Versioned<WeekMenu> = serde_json::from_str(WEEK_MENU_V1).unwrap();
+        assert_eq!(parsed.into_data(), sample_week());
+    }
+
+    #[test]
+    fn it_rejects_other_schema_versions() {
+        let json = WEEK_MENU_V1.replacen(
+            &format!("\"schema_version\": {SCHEMA_VERSION}"),
+            "\"schema_version\": 2",
+            1,
+        );
+        assert_ne!(json, WEEK_MENU_V1);
+
+        let err = serde_json::from_str::<Versioned<WeekMenu>>(&json).unwrap_err();
+        assert!(err.to_string().contains("unsupported schema version 2"));
+
+        // Snapshots from before versioning
+        let data = serde_json::to_string(&sample_week()).unwrap();
+        assert!(serde_json::from_str::<Versioned<WeekMenu>>(&data).is_err());
+    }
+}
@@ -0,0 +1,8 @@
+#[macro_use]
+extern crate lazy_static;
+
+pub mod config;
+pub mod domain;
+pub mod tg;
+#[cfg(feature = "server")]
+pub mod web;
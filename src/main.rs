@@ -1,14 +1,11 @@
-#[macro_use]
-extern crate lazy_static;
-
 use std::{
     env::{self, VarError},
     process::exit,
+    sync::Arc,
 };
 use teloxide::{dispatching::dialogue::InMemStorage, prelude::Dispatcher, Bot};
 
-mod domain;
-mod tg;
+use rwth_fressbot::{config, domain, tg};
 
 #[tokio::main]
 async fn main() {
@@ -18,11 +15,55 @@ async fn main() {
 
     log::info!("Bot token is \"{token}\"");
 
+    let config = config::Config::from_env();
+    let settings = tg::UserSettings::load(config.settings_path).await;
+    let admin_alerts = tg::AdminAlerts::new(config.admin_chat);
+
+    let html_fetcher =
+        domain::fetch::HtmlMenuFetcher::from_config(&config.http).unwrap_or_else(|e| {
+            log::error!("Invalid HTTP client configuration - {e}");
+            exit(2);
+        });
+    let html_fetcher: Arc<dyn domain::fetch::MenuProvider> = Arc::new(html_fetcher);
+    let provider = match config.openmensa.usage {
+        domain::fetch::OpenMensaUsage::Off => html_fetcher,
+        usage => {
+            let openmensa: Arc<dyn domain::fetch::MenuProvider> = Arc::new(
+                domain::fetch::OpenMensaFetcher::from_config(&config.http, &config.openmensa)
+                    .unwrap_or_else(|e| {
+                        log::error!("Invalid HTTP client configuration - {e}");
+                        exit(2);
+                    }),
+            );
+            let (primary, fallback) = match usage {
+                domain::fetch::OpenMensaUsage::Primary => (openmensa, html_fetcher),
+                _ => (html_fetcher, openmensa),
+            };
+            Arc::new(domain::fetch::FallbackMenuProvider::new(primary, fallback))
+        }
+    };
+    let mut cache = domain::fetch::MenuCache::new(provider);
+    if let Some(dir) = config.menu_cache_dir {
+        cache = cache.with_store(Arc::new(domain::fetch::JsonDirStore::new(dir)));
+    }
+    if let Some(max_stale) = config.menu_max_stale {
+        cache = cache.with_max_stale_dur(max_stale);
+    }
+    let fetcher: Arc<dyn domain::fetch::MenuProvider> = Arc::new(cache);
+    if let Some(schedule) = config.prefetch {
+        domain::fetch::Prefetcher::new(fetcher.clone(), schedule).spawn();
+    }
+    if let Some(addr) = config.server_addr {
+        spawn_server(addr, fetcher.clone());
+    }
+
     let bot = Bot::new(token);
     let mut dispatcher = Dispatcher::builder(bot, tg::handler::schema())
         .dependencies(teloxide::dptree::deps![
             InMemStorage::<tg::state::DialogueState>::new(),
-            domain::fetch::HtmlMenuFetcherWithCache::new()
+            fetcher,
+            settings,
+            admin_alerts
         ])
         .enable_ctrlc_handler()
         .build();
@@ -32,6 +73,20 @@ async fn main() {
     dispatcher.dispatch().await;
 }
 
+#[cfg(feature = "server")]
+fn spawn_server(addr: std::net::SocketAddr, fetcher: Arc<dyn domain::fetch::MenuProvider>) {
+    tokio::spawn(async move {
+        if let Err(e) = rwth_fressbot::web::serve(addr, fetcher).await {
+            log::error!("Can not serve on {addr} - {e}");
+        }
+    });
+}
+
+#[cfg(not(feature = "server"))]
+fn spawn_server(addr: std::net::SocketAddr, _: Arc<dyn domain::fetch::MenuProvider>) {
+    log::warn!("SERVER_ADDR {addr} is ignored, the bot was built without the server feature");
+}
+
 fn get_token_from_env() -> String {
     env::var("BOT_TOKEN")
         .or_else(|ref e| {
@@ -0,0 +1,74 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use teloxide::{prelude::*, types::ChatId};
+
+/// How long the same alert is held back after it has been sent.
+const ALERT_SILENCE_DUR: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Sends alerts about upstream problems to the admin chat, if one is configured.
+#[derive(Debug, Clone)]
+pub struct AdminAlerts {
+    chat: Option<ChatId>,
+    sent: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl AdminAlerts {
+    pub fn new(chat: Option<ChatId>) -> Self {
+        Self {
+            chat,
+            sent: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sends `text` to the admin chat unless an alert with the same `key` was sent recently.
+    pub async fn alert(&self, bot: &Bot, key: &str, text: String) -> anyhow::Result<()> {
+        let Some(chat) = self.chat else {
+            log::warn!("No admin chat configured. Dropping alert: {text}");
+            return Ok(());
+        };
+
+        if self.should_send(key, Instant::now()) {
+            bot.send_message(chat, text).await?;
+        }
+
+        Ok(())
+    }
+
+    fn should_send(&self, key: &str, now: Instant) -> bool {
+        let Ok(mut sent) = self.sent.lock() else {
+            return true;
+        };
+
+        match sent.get(key) {
+            Some(sent_at) if now.duration_since(*sent_at) < ALERT_SILENCE_DUR => false,
+            _ => {
+                sent.insert(key.to_owned(), now);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use teloxide::types::ChatId;
+
+    use super::{AdminAlerts, ALERT_SILENCE_DUR};
+
+    #[test]
+    fn it_holds_back_repeated_alerts() {
+        let alerts = AdminAlerts::new(Some(ChatId(42)));
+        let now = Instant::now();
+
+        assert!(alerts.should_send("layout", now));
+        assert!(!alerts.should_send("layout", now + Duration::from_secs(60)));
+        assert!(alerts.should_send("other", now));
+        assert!(alerts.should_send("layout", now + ALERT_SILENCE_DUR));
+    }
+}
@@ -8,17 +8,41 @@ use nom::{
 };
 use teloxide::utils::command::ParseError;
 
-use crate::domain::model::{Canteen, DayOfWeek};
+use crate::domain::model::{Canteen, DayOfWeek, PriceTier};
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Command {
     Cancel,
     Daily(DailyArgs),
+    PriceTier(PriceTierArg),
+}
+
+/// What `/preisgruppe` is asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriceTierArg {
+    /// Show the current price tier
+    Show,
+    Set(PriceTier),
+    /// An argument that names no price tier
+    Unknown(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DailyArgs {
     pub(super) day_of_week: DayOfWeek,
     pub(super) canteen: Option<Canteen>,
+    pub(super) view: MenuView,
+}
+
+/// How a daily menu is presented to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MenuView {
+    /// Dishes with labels and prices
+    #[default]
+    Menu,
+    /// Nutrition facts, allergens and additives of every dish
+    Details,
+    /// Dishes sorted by their CO₂ footprint
+    Climate,
 }
 
 impl Command {
@@ -30,14 +54,11 @@ impl Command {
         let command_text = command_with_botname.next().unwrap();
 
         let bot_name = command_with_botname.next();
-        match bot_name {
-            None => {}
-            Some(username) => {
-                if !username.eq_ignore_ascii_case(bot_username) {
-                    return Err(ParseError::WrongBotName(username.to_string()));
-                }
+        if let Some(username) = bot_name {
+            if !username.eq_ignore_ascii_case(bot_username) {
+                return Err(ParseError::WrongBotName(username.to_string()));
             }
-        };
+        }
 
         let args_text = words.next().unwrap_or("");
 
@@ -45,11 +66,27 @@ impl Command {
             ParseError::IncorrectFormat(anyhow!("Commands must begin with '/'").into())
         })?;
 
-        let (command_text, command) = alt((peek(parse_cancel), parse_daily))(input)
-            .map_err(|_e| ParseError::UnknownCommand(command_text.to_string()))?;
+        let (command_text, command) = alt((
+            peek(parse_cancel),
+            peek(parse_price_tier),
+            peek(parse_nutrition),
+            peek(parse_climate),
+            parse_daily,
+        ))(input)
+        .map_err(|_e| ParseError::UnknownCommand(command_text.to_string()))?;
 
         match command {
             internal::Command::Cancel => Ok(Command::Cancel),
+            internal::Command::PriceTier => {
+                let args_text = args_text.trim();
+                let arg = match PriceTier::parser().parse(args_text) {
+                    Ok((_, tier)) => PriceTierArg::Set(tier),
+                    Err(_) if args_text.is_empty() => PriceTierArg::Show,
+                    Err(_) => PriceTierArg::Unknown(args_text.to_owned()),
+                };
+
+                Ok(Command::PriceTier(arg))
+            }
             internal::Command::Daily => {
                 // TODO: Refactor into function
 
@@ -63,6 +100,25 @@ impl Command {
                 Ok(Command::Daily(DailyArgs {
                     day_of_week,
                     canteen,
+                    view: MenuView::Menu,
+                }))
+            }
+            internal::Command::Nutrition | internal::Command::Climate => {
+                let (args_text, day_of_week) =
+                    opt(|input| DayOfWeek::parser().parse(input))(args_text.trim())
+                        .map_err(|e| ParseError::Custom(e.to_owned().into()))?;
+
+                let (_, canteen) =
+                    opt(peek(|input| Canteen::parser().parse(input)))(args_text.trim())
+                        .map_err(|e| ParseError::Custom(e.to_owned().into()))?;
+
+                Ok(Command::Daily(DailyArgs {
+                    day_of_week: day_of_week.unwrap_or(DayOfWeek::Today),
+                    canteen,
+                    view: match command {
+                        internal::Command::Climate => MenuView::Climate,
+                        _ => MenuView::Details,
+                    },
                 }))
             }
         }
@@ -75,6 +131,24 @@ fn parse_cancel(input: &str) -> IResult<&str, internal::Command> {
     Ok((input, internal::Command::Cancel))
 }
 
+fn parse_price_tier(input: &str) -> IResult<&str, internal::Command> {
+    let (input, _) = tag_no_case("preisgruppe")(input)?;
+
+    Ok((input, internal::Command::PriceTier))
+}
+
+fn parse_nutrition(input: &str) -> IResult<&str, internal::Command> {
+    let (input, _) = alt((tag_no_case("naehrwerte"), tag_no_case("nährwerte")))(input)?;
+
+    Ok((input, internal::Command::Nutrition))
+}
+
+fn parse_climate(input: &str) -> IResult<&str, internal::Command> {
+    let (input, _) = alt((tag_no_case("klima"), tag_no_case("co2")))(input)?;
+
+    Ok((input, internal::Command::Climate))
+}
+
 fn parse_daily(input: &str) -> IResult<&str, internal::Command> {
     let (input, _) = peek(|input| DayOfWeek::parser().parse(input))(input)?;
 
@@ -84,12 +158,18 @@ mod internal {
     pub enum Command {
         Cancel,
         Daily,
+        Nutrition,
+        Climate,
+        PriceTier,
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{domain::model::DayOfWeek, tg::command::DailyArgs};
+    use crate::{
+        domain::model::{Canteen, DayOfWeek, PriceTier},
+        tg::command::{DailyArgs, MenuView, PriceTierArg},
+    };
 
     use super::Command;
 
@@ -104,7 +184,52 @@ mod test {
             parsed.unwrap(),
             Command::Daily(DailyArgs {
                 day_of_week: DayOfWeek::Today,
-                canteen: None
+                canteen: None,
+                view: MenuView::Menu,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_price_tier_command() {
+        assert_eq!(
+            Command::parse("/preisgruppe mitarbeiter", "mybotname").unwrap(),
+            Command::PriceTier(PriceTierArg::Set(PriceTier::Employee))
+        );
+        assert_eq!(
+            Command::parse("/preisgruppe", "mybotname").unwrap(),
+            Command::PriceTier(PriceTierArg::Show)
+        );
+        assert_eq!(
+            Command::parse("/preisgruppe rentner", "mybotname").unwrap(),
+            Command::PriceTier(PriceTierArg::Unknown("rentner".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_nutrition_command() {
+        assert_eq!(
+            Command::parse("/naehrwerte morgen academica", "mybotname").unwrap(),
+            Command::Daily(DailyArgs {
+                day_of_week: DayOfWeek::Tomorrow,
+                canteen: Some(Canteen::Academica),
+                view: MenuView::Details,
+            })
+        );
+        assert_eq!(
+            Command::parse("/klima vita", "mybotname").unwrap(),
+            Command::Daily(DailyArgs {
+                day_of_week: DayOfWeek::Today,
+                canteen: Some(Canteen::Vita),
+                view: MenuView::Climate,
+            })
+        );
+        assert_eq!(
+            Command::parse("/naehrwerte vita", "mybotname").unwrap(),
+            Command::Daily(DailyArgs {
+                day_of_week: DayOfWeek::Today,
+                canteen: Some(Canteen::Vita),
+                view: MenuView::Details,
             })
         );
     }
@@ -15,7 +15,7 @@ pub mod handler {
     use crate::{
         domain::fetch::err::FetcherError,
         domain::model::Canteen,
-        tg::command::{Command, DailyArgs},
+        tg::command::{Command, DailyArgs, MenuView},
     };
 
     type BotDialogue = Dialogue<state::DialogueState, InMemStorage<DialogueState>>;
@@ -31,6 +31,10 @@ pub mod handler {
                         res.ok()
                     },
                 )
+                .branch(
+                    dptree::filter(|args: DailyArgs| args.view != MenuView::Menu)
+                        .endpoint(handler::endpoint::menu_view_by_date),
+                )
                 .endpoint(handler::endpoint::menu_by_date),
             )
             .branch(
@@ -41,11 +45,43 @@ pub mod handler {
                 )
                 .branch(
                     dptree::filter_map(|err: std::sync::Arc<anyhow::Error>| {
-                        err.downcast_ref::<FetcherError>().map(|err| err.clone())
+                        err.downcast_ref::<FetcherError>().cloned()
                     })
                     .branch(
                         dptree::case![FetcherError::CanteenClosed { canteen, date }]
                             .endpoint(handler::endpoint::err_canteen_closed),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::NotPublished { canteen, date }]
+                            .endpoint(handler::endpoint::err_menu_not_published),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::UnsupportedCanteen { canteen }]
+                            .endpoint(handler::endpoint::err_unsupported_canteen),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::LayoutChanged { canteen, reason }]
+                            .endpoint(handler::endpoint::err_layout_changed),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::Timeout]
+                            .endpoint(handler::endpoint::err_timeout),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::HttpStatus(status)]
+                            .endpoint(handler::endpoint::err_http_status),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::Network(cause)]
+                            .endpoint(handler::endpoint::err_network),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::CircuitOpen { host }]
+                            .endpoint(handler::endpoint::err_circuit_open),
+                    )
+                    .branch(
+                        dptree::case![FetcherError::Parse(cause)]
+                            .endpoint(handler::endpoint::err_parse),
                     ),
                 )
                 .chain(dptree::inspect(|err: std::sync::Arc<anyhow::Error>| {
@@ -69,7 +105,8 @@ pub mod handler {
                 )
                 .endpoint(handler::endpoint::ask_canteen),
         )
-        .branch(dptree::case![Command::Cancel].endpoint(handler::endpoint::cancel));
+        .branch(dptree::case![Command::Cancel].endpoint(handler::endpoint::cancel))
+        .branch(dptree::case![Command::PriceTier(arg)].endpoint(handler::endpoint::price_tier));
 
         let message_handler = Update::filter_message()
             .branch(command_handler)
@@ -91,14 +128,17 @@ pub mod handler {
             .branch(message_handler)
     }
 
+    #[allow(clippy::module_inception)]
     pub mod handler {
         pub mod proj {
+            use std::sync::Arc;
+
             use chrono::NaiveDate;
 
             use teloxide::prelude::*;
 
             use crate::{
-                domain::fetch::HtmlMenuFetcherWithCache,
+                domain::fetch::MenuProvider,
                 domain::model::{Canteen, DayOfWeek, Menu},
                 tg::command::DailyArgs,
             };
@@ -107,6 +147,7 @@ pub mod handler {
                 let DailyArgs {
                     day_of_week,
                     canteen,
+                    ..
                 } = args;
 
                 canteen.map(|canteen| (day_of_week, canteen))
@@ -118,16 +159,14 @@ pub mod handler {
 
             pub async fn fetch_daily_menu(
                 args: (NaiveDate, Canteen),
-                fetcher: HtmlMenuFetcherWithCache,
+                fetcher: Arc<dyn MenuProvider>,
             ) -> Result<Menu, std::sync::Arc<anyhow::Error>> {
                 let (date, canteen) = args;
 
-                let res = fetcher
+                fetcher
                     .fetch_daily_menu(date, canteen)
                     .await
-                    .map_err(|e| std::sync::Arc::new(e));
-
-                res
+                    .map_err(std::sync::Arc::new)
             }
 
             pub fn parse_canteen_from_msg(msg: Message) -> Option<Canteen> {
@@ -154,9 +193,10 @@ pub mod handler {
             use crate::{
                 domain::model::{Canteen, Menu},
                 tg::{
-                    command::DailyArgs,
+                    command::{DailyArgs, MenuView, PriceTierArg},
                     handler::{BotDialogue, HandlerResult},
                     state::DialogueState,
+                    AdminAlerts, UserSettings,
                 },
             };
 
@@ -165,7 +205,7 @@ pub mod handler {
                 message: Message,
                 dialogue: BotDialogue,
             ) -> HandlerResult {
-                if let Some(state) = dialogue.get_or_default().await.ok() {
+                if let Ok(state) = dialogue.get_or_default().await {
                     match state {
                         DialogueState::Noop => {}
                         DialogueState::Daily {
@@ -217,6 +257,154 @@ pub mod handler {
                 Ok(())
             }
 
+            pub async fn err_menu_not_published(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                (date, _): (NaiveDate, Canteen),
+            ) -> HandlerResult {
+                let reply = format!(
+                    "Für {} wurde noch kein Speiseplan veröffentlicht. 🤷",
+                    date.format_localized("%A, %d.%m.%Y", chrono::Locale::de_DE)
+                );
+                dialogue.reset().await?;
+
+                bot.send_message(msg.chat.id, reply)
+                    .reply_to_message_id(reply_id)
+                    .reply_markup(ReplyMarkup::KeyboardRemove(
+                        KeyboardRemove::new().selective(true),
+                    ))
+                    .await?;
+
+                Ok(())
+            }
+
+            /// Tells the user that the menu can not be read at the moment and alerts the admins.
+            pub async fn err_layout_changed(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                admin_alerts: AdminAlerts,
+                (canteen, reason): (Canteen, String),
+            ) -> HandlerResult {
+                log::error!("Menu page of {canteen} changed its layout: {reason}");
+
+                let alert = format!(
+                    "⚠️ Der Speiseplan der Mensa {canteen} kann nicht mehr gelesen werden: {reason}"
+                );
+                if let Err(e) = admin_alerts.alert(&bot, &reason, alert).await {
+                    log::error!("Can not alert the admin chat: {e}");
+                }
+
+                dialogue.reset().await?;
+
+                bot.send_message(
+                    msg.chat.id,
+                    "Der Speiseplan ist vorübergehend nicht verfügbar. 🛠",
+                )
+                .reply_to_message_id(reply_id)
+                .reply_markup(ReplyMarkup::KeyboardRemove(
+                    KeyboardRemove::new().selective(true),
+                ))
+                .await?;
+
+                Ok(())
+            }
+
+            pub async fn err_timeout(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+            ) -> HandlerResult {
+                log::warn!("Request to the menu page timed out");
+
+                let reply = "Die Seite des Studierendenwerks antwortet gerade nicht. ⏳ \
+                    Bitte versuche es gleich noch einmal.";
+
+                send_failure(bot, msg, reply_id, dialogue, reply).await
+            }
+
+            pub async fn err_http_status(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                status: u16,
+            ) -> HandlerResult {
+                log::warn!("Menu page responded with HTTP status {status}");
+
+                let reply = if status >= 500 {
+                    format!(
+                        "Die Seite des Studierendenwerks hat gerade Probleme (HTTP {status}). 🚧 \
+                        Bitte versuche es später noch einmal."
+                    )
+                } else {
+                    format!("Der Speiseplan wurde nicht gefunden (HTTP {status}). 🤷")
+                };
+
+                send_failure(bot, msg, reply_id, dialogue, &reply).await
+            }
+
+            pub async fn err_network(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                cause: String,
+            ) -> HandlerResult {
+                log::warn!("Can not reach the menu page: {cause}");
+
+                let reply = "Ich kann das Studierendenwerk gerade nicht erreichen. 📡 \
+                    Bitte versuche es später noch einmal.";
+
+                send_failure(bot, msg, reply_id, dialogue, reply).await
+            }
+
+            pub async fn err_circuit_open(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                host: String,
+            ) -> HandlerResult {
+                log::info!("Not fetching from {host} while its circuit is open");
+
+                let reply = "Das Studierendenwerk ist gerade nicht erreichbar. 🔌 \
+                    Bitte versuche es in ein paar Minuten noch einmal.";
+
+                send_failure(bot, msg, reply_id, dialogue, reply).await
+            }
+
+            pub async fn err_unsupported_canteen(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                canteen: Canteen,
+            ) -> HandlerResult {
+                let reply =
+                    format!("Für die Mensa {canteen} kenne ich leider keinen Speiseplan. 🤷");
+
+                send_failure(bot, msg, reply_id, dialogue, &reply).await
+            }
+
+            pub async fn err_parse(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                cause: String,
+            ) -> HandlerResult {
+                log::error!("Can not read the menu page: {cause}");
+
+                let reply = "Der Speiseplan konnte nicht gelesen werden. 🤔";
+
+                send_failure(bot, msg, reply_id, dialogue, reply).await
+            }
+
             /// Sends a generic message about a failed command to the user and resets the dialogue state.
             pub async fn generic_failure(
                 bot: Bot,
@@ -224,8 +412,24 @@ pub mod handler {
                 reply_id: MessageId,
                 dialogue: BotDialogue,
             ) -> HandlerResult {
-                let reply = "Whoops. Something went wrong.";
+                send_failure(
+                    bot,
+                    msg,
+                    reply_id,
+                    dialogue,
+                    "Whoops. Something went wrong.",
+                )
+                .await
+            }
 
+            /// Replies to the failed command with `reply` and resets the dialogue state.
+            async fn send_failure(
+                bot: Bot,
+                msg: Message,
+                reply_id: MessageId,
+                dialogue: BotDialogue,
+                reply: &str,
+            ) -> HandlerResult {
                 dialogue.reset().await?;
 
                 bot.send_message(msg.chat.id, reply)
@@ -243,14 +447,20 @@ pub mod handler {
                 msg: Message,
                 dialogue: BotDialogue,
                 reply_id: MessageId,
+                settings: UserSettings,
                 (date, canteen): (NaiveDate, Canteen),
                 menu: Menu,
             ) -> HandlerResult {
+                let tier = settings.price_tier(msg.from().map(|user| user.id)).await;
+
                 let date_fmt = date.format_localized("%A, %d.%m.%Y", chrono::Locale::de_DE);
-                let reply = format!(
+                let mut reply = format!(
                     "<strong>Plan für Mensa {} – {}</strong>\n\n",
                     canteen, date_fmt
-                ) + &menu.fmt_html()?;
+                ) + &menu.fmt_html(tier)?;
+                if let Some(note) = menu.fmt_stale_note_html(chrono::Utc::now()) {
+                    reply += &format!("\n\n{note}");
+                }
 
                 bot.send_message(msg.chat.id, reply)
                     .parse_mode(ParseMode::Html)
@@ -265,6 +475,78 @@ pub mod handler {
                 Ok(())
             }
 
+            /// Sends the menu in one of the views that do not depend on the user's settings.
+            pub async fn menu_view_by_date(
+                bot: Bot,
+                msg: Message,
+                dialogue: BotDialogue,
+                reply_id: MessageId,
+                args: DailyArgs,
+                (date, canteen): (NaiveDate, Canteen),
+                menu: Menu,
+            ) -> HandlerResult {
+                let date_fmt = date.format_localized("%A, %d.%m.%Y", chrono::Locale::de_DE);
+                let (title, body) = match args.view {
+                    MenuView::Climate => ("CO₂-Bilanz", menu.fmt_climate_html()?),
+                    MenuView::Details | MenuView::Menu => ("Nährwerte", menu.fmt_details_html()?),
+                };
+                let mut reply = format!(
+                    "<strong>{} für Mensa {} – {}</strong>\n\n",
+                    title, canteen, date_fmt
+                ) + &body;
+                if let Some(note) = menu.fmt_stale_note_html(chrono::Utc::now()) {
+                    reply += &format!("\n\n{note}");
+                }
+
+                bot.send_message(msg.chat.id, reply)
+                    .parse_mode(ParseMode::Html)
+                    .reply_to_message_id(reply_id)
+                    .reply_markup(ReplyMarkup::KeyboardRemove(
+                        KeyboardRemove::new().selective(true),
+                    ))
+                    .await?;
+
+                dialogue.reset().await?;
+
+                Ok(())
+            }
+
+            /// Shows the price tier of the sender or changes it as asked by `arg`.
+            pub async fn price_tier(
+                bot: Bot,
+                msg: Message,
+                settings: UserSettings,
+                arg: PriceTierArg,
+            ) -> HandlerResult {
+                let Some(user) = msg.from() else {
+                    return Ok(());
+                };
+
+                let reply = match arg {
+                    PriceTierArg::Set(tier) => {
+                        settings.set_price_tier(user.id, tier).await?;
+                        format!("Du siehst ab jetzt die Preise für {tier}. 💶")
+                    }
+                    PriceTierArg::Unknown(arg) => format!(
+                        "Die Preisgruppe „{arg}“ kenne ich nicht. 🤔\n\
+                        Wähle /preisgruppe studierende, bedienstete oder gäste."
+                    ),
+                    PriceTierArg::Show => {
+                        let current = settings.price_tier(Some(user.id)).await;
+                        format!(
+                            "Du siehst die Preise für {current}.\n\
+                            Ändern mit /preisgruppe studierende, bedienstete oder gäste."
+                        )
+                    }
+                };
+
+                bot.send_message(msg.chat.id, reply)
+                    .reply_to_message_id(msg.id)
+                    .await?;
+
+                Ok(())
+            }
+
             pub async fn ask_canteen(
                 bot: Bot,
                 msg: Message,
@@ -275,7 +557,7 @@ pub mod handler {
                 dialogue
                     .update(DialogueState::Daily {
                         message_id: reply_id,
-                        args: args,
+                        args,
                     })
                     .await?;
 
@@ -288,7 +570,7 @@ pub mod handler {
                         KeyboardMarkup::new(canteen_btns)
                             .one_time_keyboard(Some(true))
                             .selective(Some(true))
-                            .input_field_placeholder(format!("Mensa auswählen")),
+                            .input_field_placeholder("Mensa auswählen".to_string()),
                     ))
                     .await?;
 
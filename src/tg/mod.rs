@@ -1,6 +1,12 @@
+mod admin;
+
 mod command;
 
 mod dispatch;
 
+mod settings;
+
+pub use admin::AdminAlerts;
 pub use dispatch::handler;
 pub use dispatch::handler::state;
+pub use settings::UserSettings;
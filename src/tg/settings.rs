@@ -0,0 +1,119 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use teloxide::types::UserId;
+use tokio::sync::Mutex;
+
+use crate::domain::model::PriceTier;
+
+/// Settings of each user, persisted as JSON so they survive restarts.
+#[derive(Debug, Clone)]
+pub struct UserSettings {
+    path: PathBuf,
+    prefs: Arc<Mutex<HashMap<UserId, UserPrefs>>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct UserPrefs {
+    #[serde(default)]
+    price_tier: PriceTier,
+}
+
+impl UserSettings {
+    /// Loads the settings stored at `path`. A missing or unreadable file yields empty settings.
+    pub async fn load(path: PathBuf) -> Self {
+        let prefs = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                log::error!("Can not parse settings file {}: {e}", path.display());
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                log::info!("No settings file at {}. Starting fresh", path.display());
+                HashMap::new()
+            }
+            Err(e) => {
+                log::error!("Can not read settings file {}: {e}", path.display());
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path,
+            prefs: Arc::new(Mutex::new(prefs)),
+        }
+    }
+
+    /// Returns the price tier of `user`, or the default tier for unknown users.
+    pub async fn price_tier(&self, user: Option<UserId>) -> PriceTier {
+        let Some(user) = user else {
+            return PriceTier::default();
+        };
+
+        self.prefs
+            .lock()
+            .await
+            .get(&user)
+            .map(|prefs| prefs.price_tier)
+            .unwrap_or_default()
+    }
+
+    pub async fn set_price_tier(&self, user: UserId, tier: PriceTier) -> anyhow::Result<()> {
+        let mut prefs = self.prefs.lock().await;
+        prefs.entry(user).or_default().price_tier = tier;
+
+        // Keep the lock while writing so concurrent updates are written in order
+        self.save(&prefs).await
+    }
+
+    async fn save(&self, prefs: &HashMap<UserId, UserPrefs>) -> anyhow::Result<()> {
+        let json = serde_json::to_vec_pretty(prefs)?;
+
+        // Write to a temporary file first so a crash never leaves a truncated settings file
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, json).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use teloxide::types::UserId;
+
+    use crate::domain::model::PriceTier;
+
+    use super::UserSettings;
+
+    #[tokio::test]
+    async fn it_persists_price_tiers() {
+        let path = std::env::temp_dir().join(format!(
+            "fressbot-settings-test-{}.json",
+            std::process::id()
+        ));
+
+        let settings = UserSettings::load(path.clone()).await;
+        assert_eq!(
+            settings.price_tier(Some(UserId(42))).await,
+            PriceTier::Student
+        );
+
+        settings
+            .set_price_tier(UserId(42), PriceTier::Employee)
+            .await
+            .unwrap();
+
+        let reloaded = UserSettings::load(path.clone()).await;
+        assert_eq!(
+            reloaded.price_tier(Some(UserId(42))).await,
+            PriceTier::Employee
+        );
+        assert_eq!(
+            reloaded.price_tier(Some(UserId(7))).await,
+            PriceTier::Student
+        );
+        assert_eq!(reloaded.price_tier(None).await, PriceTier::Student);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::domain::{
+    fetch::{err::FetcherError, FetchMetricsSnapshot, MenuProvider, MenuWeek},
+    model::{Canteen, Menu, Versioned, WeekMenu},
+};
+
+use super::canteen_of_slug;
+
+/// A canteen as listed by `GET /canteens`.
+#[derive(Debug, Serialize)]
+pub(super) struct CanteenInfo {
+    /// Identifies the canteen in the other routes
+    id: Canteen,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct WeekQuery {
+    #[serde(default = "current_week")]
+    week: MenuWeek,
+}
+
+fn current_week() -> MenuWeek {
+    MenuWeek::Current
+}
+
+/// A menu as the API serves it.
+#[derive(Debug, Serialize)]
+pub(super) struct Served<T> {
+    #[serde(flatten)]
+    menu: T,
+    /// When the menu was fetched, if it is served from stale data while it is refreshed
+    stale_since: Option<DateTime<Utc>>,
+}
+
+/// Lists all canteens.
+pub(super) async fn canteens() -> Json<Versioned<Vec<CanteenInfo>>> {
+    let canteens = Canteen::iter()
+        .map(|canteen| CanteenInfo {
+            id: canteen,
+            name: canteen.to_string(),
+        })
+        .collect();
+
+    Json(Versioned::new(canteens))
+}
+
+/// Serves the menu of a canteen on a date given as "YYYY-MM-DD".
+pub(super) async fn daily_menu(
+    State(provider): State<Arc<dyn MenuProvider>>,
+    Path((slug, date)): Path<(String, String)>,
+) -> Result<Json<Versioned<Served<Menu>>>, ApiError> {
+    let canteen = find_canteen(&slug)?;
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        code: "invalid_date",
+        message: format!("\"{date}\" is not a date like 2024-10-14: {e}"),
+    })?;
+
+    let menu = provider.fetch_daily_menu(date, canteen).await?;
+    Ok(Json(Versioned::new(Served {
+        stale_since: menu.stale_since(),
+        menu,
+    })))
+}
+
+/// Serves the menus of a canteen for the current week, or the next one with `?week=next`.
+pub(super) async fn weekly_menu(
+    State(provider): State<Arc<dyn MenuProvider>>,
+    Path(slug): Path<String>,
+    Query(query): Query<WeekQuery>,
+) -> Result<Json<Versioned<Served<WeekMenu>>>, ApiError> {
+    let canteen = find_canteen(&slug)?;
+
+    let week_menu = provider.fetch_weekly_menu(canteen, query.week).await?;
+    Ok(Json(Versioned::new(Served {
+        stale_since: week_menu.is_stale().then(|| week_menu.fetched_at()),
+        menu: week_menu,
+    })))
+}
+
+/// Serves the counters of the requests made to the menu source.
+pub(super) async fn metrics(
+    State(provider): State<Arc<dyn MenuProvider>>,
+) -> Result<Json<FetchMetricsSnapshot>, ApiError> {
+    provider.metrics().map(Json).ok_or_else(|| ApiError {
+        status: StatusCode::NOT_FOUND,
+        code: "no_metrics",
+        message: "the menu source keeps no metrics".to_owned(),
+    })
+}
+
+fn find_canteen(slug: &str) -> Result<Canteen, ApiError> {
+    canteen_of_slug(slug).ok_or_else(|| ApiError {
+        status: StatusCode::NOT_FOUND,
+        code: "unknown_canteen",
+        message: format!("no canteen has the id \"{slug}\""),
+    })
+}
+
+/// An error as the API responds with it, e.g. `{"error": "canteen_closed", "message": …}`.
+#[derive(Debug)]
+pub(super) struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+/// Days without food are not found, failures of the menu pages are bad gateways.
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        let (status, code) = match e.downcast_ref::<FetcherError>() {
+            Some(FetcherError::CanteenClosed { .. }) => (StatusCode::NOT_FOUND, "canteen_closed"),
+            Some(FetcherError::NotPublished { .. }) => (StatusCode::NOT_FOUND, "not_published"),
+            Some(FetcherError::UnsupportedCanteen { .. }) => {
+                (StatusCode::NOT_FOUND, "unsupported_canteen")
+            }
+            Some(_) => (StatusCode::BAD_GATEWAY, "upstream"),
+            None => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+        };
+
+        if status.is_server_error() {
+            log::warn!("Can not serve a menu - {e}");
+        }
+
+        Self {
+            status,
+            code,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: &'static str,
+            message: String,
+        }
+
+        let body = Body {
+            error: self.code,
+            message: self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+    use chrono::{Datelike, NaiveDate};
+    use serde_json::Value;
+
+    use crate::domain::{
+        fetch::{HtmlMenuFetcher, InMemoryMenuProvider, MenuCache, MenuProvider, MenuWeek},
+        model::{
+            menu::{Category, Dish, MenuExtra},
+            Canteen, DayMenu, Menu, WeekMenu,
+        },
+    };
+
+    fn this_monday() -> NaiveDate {
+        let today = chrono::Local::now().date_naive();
+        today - chrono::Days::new(today.weekday().num_days_from_monday().into())
+    }
+
+    fn week_with_closed_tuesday() -> WeekMenu {
+        let dish = Dish::new("Currywurst".to_owned(), vec![], vec![], None);
+        let menu =
+            Menu::new::<MenuExtra>(BTreeMap::from([(Category::Classic, vec![dish])]), vec![]);
+
+        WeekMenu::new(BTreeMap::from([
+            (this_monday(), DayMenu::Open(menu)),
+            (this_monday() + chrono::Days::new(1), DayMenu::Closed),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn it_serves_menus_as_json() {
+        let provider = InMemoryMenuProvider::new();
+        provider.insert(Canteen::Vita, MenuWeek::Current, week_with_closed_tuesday());
+        let addr = crate::web::spawn_local(Arc::new(provider)).await;
+
+        let get = |path: String| async move {
+            let resp = reqwest::get(format!("http://{addr}{path}")).await.unwrap();
+            let status = resp.status().as_u16();
+            let body = resp.bytes().await.unwrap();
+            (status, serde_json::from_slice::<Value>(&body).unwrap())
+        };
+
+        let (status, canteens) = get("/canteens".into()).await;
+        assert_eq!(status, 200);
+        assert_eq!(canteens["schema_version"], 1);
+        assert_eq!(canteens["data"][1]["id"], "ahornstrasse");
+        assert_eq!(canteens["data"][1]["name"], "Ahornstraße");
+
+        let (status, menu) = get(format!("/canteens/vita/menu/{}", this_monday())).await;
+        assert_eq!(status, 200);
+        assert_eq!(menu["data"]["dishes"]["Klassiker"][0]["name"], "Currywurst");
+
+        assert_eq!(menu["data"]["stale_since"], Value::Null);
+
+        let (status, week) = get("/canteens/vita/week".into()).await;
+        assert_eq!(status, 200);
+        assert_eq!(week["data"]["days"].as_object().unwrap().len(), 2);
+        assert_eq!(week["data"]["stale_since"], Value::Null);
+
+        let tuesday = this_monday() + chrono::Days::new(1);
+        let (status, err) = get(format!("/canteens/vita/menu/{tuesday}")).await;
+        assert_eq!(status, 404);
+        assert_eq!(err["error"], "canteen_closed");
+
+        let (status, err) = get("/canteens/mcdonalds/week".into()).await;
+        assert_eq!(status, 404);
+        assert_eq!(err["error"], "unknown_canteen");
+
+        let (status, err) = get("/canteens/vita/menu/monday".into()).await;
+        assert_eq!(status, 400);
+        assert_eq!(err["error"], "invalid_date");
+
+        // The next week of the vita is not in memory, which the provider fails with status 404
+        let (status, err) = get("/canteens/vita/week?week=next".into()).await;
+        assert_eq!(status, 502);
+        assert_eq!(err["error"], "upstream");
+    }
+
+    #[tokio::test]
+    async fn it_serves_fetch_metrics() {
+        let fetcher = MenuCache::new(Arc::new(HtmlMenuFetcher::new()));
+        let addr = crate::web::spawn_local(Arc::new(fetcher)).await;
+
+        let resp = reqwest::get(format!("http://{addr}/metrics"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+        let metrics: Value = serde_json::from_slice(&resp.bytes().await.unwrap()).unwrap();
+        assert_eq!(metrics["requests"], 0);
+        assert_eq!(metrics["circuit_opened"], 0);
+
+        let addr = crate::web::spawn_local(Arc::new(InMemoryMenuProvider::new())).await;
+        let resp = reqwest::get(format!("http://{addr}/metrics"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 404);
+    }
+
+    #[tokio::test]
+    async fn it_tells_when_menus_are_stale() {
+        let provider = InMemoryMenuProvider::new();
+        provider.insert(Canteen::Vita, MenuWeek::Current, week_with_closed_tuesday());
+        let cache = MenuCache::new(Arc::new(provider)).with_fresh_dur(Duration::ZERO);
+        let week_menu = cache
+            .fetch_weekly_menu(Canteen::Vita, MenuWeek::Current)
+            .await
+            .unwrap();
+        let addr = crate::web::spawn_local(Arc::new(cache)).await;
+
+        let get = |path: String| async move {
+            let resp = reqwest::get(format!("http://{addr}{path}")).await.unwrap();
+            serde_json::from_slice::<Value>(&resp.bytes().await.unwrap()).unwrap()
+        };
+        let fetched_at = serde_json::to_value(week_menu.fetched_at()).unwrap();
+
+        let menu = get(format!("/canteens/vita/menu/{}", this_monday())).await;
+        assert_eq!(menu["data"]["stale_since"], fetched_at);
+        assert_eq!(menu["data"]["dishes"]["Klassiker"][0]["name"], "Currywurst");
+
+        let week = get("/canteens/vita/week".into()).await;
+        assert_eq!(week["data"]["stale_since"], fetched_at);
+    }
+}
@@ -0,0 +1,52 @@
+mod api;
+mod openmensa_feed;
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{routing::get, Router};
+
+use crate::domain::{fetch::MenuProvider, model::Canteen};
+
+pub use openmensa_feed::render_feed;
+
+/// Returns the routes that publish the menus of `provider`, as JSON and as OpenMensa feeds.
+pub fn router(provider: Arc<dyn MenuProvider>) -> Router {
+    Router::new()
+        .route("/canteens", get(api::canteens))
+        .route("/canteens/{canteen}/menu/{date}", get(api::daily_menu))
+        .route("/canteens/{canteen}/week", get(api::weekly_menu))
+        .route("/metrics", get(api::metrics))
+        .route(
+            "/openmensa/{canteen}/feed.xml",
+            get(openmensa_feed::canteen_feed),
+        )
+        .with_state(provider)
+}
+
+/// Serves the routes on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, provider: Arc<dyn MenuProvider>) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    log::info!("Serving menus on http://{addr}");
+
+    axum::serve(listener, router(provider)).await?;
+    Ok(())
+}
+
+/// Finds the canteen by the name it has in URLs, see [`Canteen::slug`].
+fn canteen_of_slug(slug: &str) -> Option<Canteen> {
+    use strum::IntoEnumIterator;
+
+    Canteen::iter().find(|canteen| canteen.slug() == slug)
+}
+
+/// Serves the routes on a free local port and returns its address.
+#[cfg(test)]
+async fn spawn_local(provider: Arc<dyn MenuProvider>) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let router = router(provider);
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    addr
+}
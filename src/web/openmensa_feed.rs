@@ -0,0 +1,337 @@
+use std::{
+    fmt::{self, Write},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::domain::{
+    fetch::{err::FetcherError, MenuProvider, MenuWeek},
+    model::{
+        menu::{Dish, Price, SideDish},
+        Canteen, DayMenu, PriceTier, WeekMenu,
+    },
+};
+
+use super::canteen_of_slug;
+
+const FEED_ROOT: &str = concat!(
+    r#"<openmensa version="2.1" xmlns="http://openmensa.org/open-mensa-v2" "#,
+    r#"xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" "#,
+    r#"xsi:schemaLocation="http://openmensa.org/open-mensa-v2 "#,
+    r#"http://openmensa.org/open-mensa-v2.xsd">"#
+);
+
+/// OpenMensa limits names and notes to this many characters.
+const MAX_TEXT_LEN: usize = 250;
+
+/// Serves the menus of both week pages of a canteen as an OpenMensa feed.
+///
+/// A next week that is not published yet is left out of the feed.
+pub(super) async fn canteen_feed(
+    State(provider): State<Arc<dyn MenuProvider>>,
+    Path(slug): Path<String>,
+) -> Response {
+    let Some(canteen) = canteen_of_slug(&slug) else {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("no canteen named \"{slug}\""),
+        )
+            .into_response();
+    };
+
+    let current = match provider.fetch_weekly_menu(canteen, MenuWeek::Current).await {
+        Ok(week) => week,
+        Err(e) => {
+            let status = match e.downcast_ref::<FetcherError>() {
+                Some(FetcherError::UnsupportedCanteen { .. }) => StatusCode::NOT_FOUND,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            log::warn!("Can not serve the feed of {canteen} - {e}");
+            return (status, e.to_string()).into_response();
+        }
+    };
+    let next = provider
+        .fetch_weekly_menu(canteen, MenuWeek::Next)
+        .await
+        .inspect_err(|e| log::debug!("Leaving the next week out of the feed of {canteen} - {e}"))
+        .ok();
+
+    let weeks: Vec<_> = [Some(current), next].into_iter().flatten().collect();
+    match render_feed(canteen, &weeks) {
+        Ok(feed) => ([(header::CONTENT_TYPE, "application/xml")], feed).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Renders the days of `weeks` as an OpenMensa feed v2.1 document.
+///
+/// Open days without any dish are left out, as the feed can not express them.
+pub fn render_feed(canteen: Canteen, weeks: &[WeekMenu]) -> Result<String, fmt::Error> {
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(xml, "{FEED_ROOT}")?;
+    writeln!(xml, "  <canteen>")?;
+    writeln!(xml, "    <name>{}</name>", text(canteen.as_ref()))?;
+
+    for (date, day) in weeks.iter().flat_map(|week| week.days()) {
+        match day {
+            DayMenu::Closed => {
+                writeln!(xml, r#"    <day date="{date}"><closed/></day>"#)?;
+            }
+            DayMenu::Open(menu) if menu.dishes().next().is_none() => {}
+            DayMenu::Open(menu) => {
+                writeln!(xml, r#"    <day date="{date}">"#)?;
+                for (categ, dishes) in menu.categories().filter(|(_, d)| !d.is_empty()) {
+                    writeln!(
+                        xml,
+                        r#"      <category name="{}">"#,
+                        text(&categ.to_string())
+                    )?;
+                    for dish in dishes {
+                        write_dish(&mut xml, dish)?;
+                    }
+                    writeln!(xml, "      </category>")?;
+                }
+                for extra in menu.extras().iter().filter(|e| !e.options().is_empty()) {
+                    writeln!(xml, r#"      <category name="{}">"#, text(extra.category()))?;
+                    for option in extra.options() {
+                        write_side_dish(&mut xml, option)?;
+                    }
+                    writeln!(xml, "      </category>")?;
+                }
+                writeln!(xml, "    </day>")?;
+            }
+        }
+    }
+
+    writeln!(xml, "  </canteen>")?;
+    writeln!(xml, "</openmensa>")?;
+    Ok(xml)
+}
+
+fn write_dish(xml: &mut String, dish: &Dish) -> fmt::Result {
+    let name = match dish.ingreds() {
+        [] => dish.name().to_owned(),
+        ingreds => format!("{} | {}", dish.name(), ingreds.join(", ")),
+    };
+
+    let notes = dish
+        .labels()
+        .iter()
+        .map(|label| label.name().to_owned())
+        .chain(dish.allergens().iter().map(ToString::to_string))
+        .chain(dish.additives().iter().map(ToString::to_string));
+
+    write_meal(xml, &name, notes, dish.price())
+}
+
+fn write_side_dish(xml: &mut String, option: &SideDish) -> fmt::Result {
+    let notes = option
+        .labels()
+        .iter()
+        .map(|label| label.name().to_owned())
+        .chain(option.allergens().iter().map(ToString::to_string))
+        .chain(option.additives().iter().map(ToString::to_string));
+
+    write_meal(xml, option.name(), notes, None)
+}
+
+fn write_meal(
+    xml: &mut String,
+    name: &str,
+    notes: impl Iterator<Item = String>,
+    price: Option<&Price>,
+) -> fmt::Result {
+    writeln!(xml, "        <meal>")?;
+    writeln!(xml, "          <name>{}</name>", text(name))?;
+    for note in notes {
+        writeln!(xml, "          <note>{}</note>", text(&note))?;
+    }
+
+    let roles = [
+        (PriceTier::Student, "student"),
+        (PriceTier::Employee, "employee"),
+        (PriceTier::Guest, "other"),
+    ];
+    for (tier, role) in roles {
+        if let Some(cents) = price.and_then(|price| price.get(tier)) {
+            writeln!(
+                xml,
+                r#"          <price role="{role}">{}.{:02}</price>"#,
+                cents / 100,
+                cents % 100
+            )?;
+        }
+    }
+
+    writeln!(xml, "        </meal>")
+}
+
+/// Escapes `s` for element content and attribute values, cut to the length OpenMensa allows.
+fn text(s: &str) -> String {
+    let s = s.trim();
+    if s.chars().count() <= MAX_TEXT_LEN {
+        return escape(s);
+    }
+
+    let cut: String = s.chars().take(MAX_TEXT_LEN - 1).collect();
+    escape(&format!("{}…", cut.trim_end()))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::BTreeMap,
+        io::{ErrorKind, Write},
+        process::{Command, Stdio},
+        sync::Arc,
+    };
+
+    use chrono::NaiveDate;
+    use scraper::Html;
+
+    use crate::domain::{
+        fetch::{HtmlMenuFetcher, InMemoryMenuProvider, MenuWeek},
+        model::{
+            menu::{Category, Dish, Label, MenuExtra, Price},
+            Canteen, DayMenu, Menu, WeekMenu,
+        },
+    };
+
+    use super::render_feed;
+
+    const ACADEMICA_WEEK: &str = include_str!("../../fixtures/html/academica-w.html");
+    const FEED_SCHEMA: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/openmensa/open-mensa-v2.xsd"
+    );
+
+    fn academica_week() -> WeekMenu {
+        let (week, _) = HtmlMenuFetcher::new().parse_week(&Html::parse_document(ACADEMICA_WEEK));
+        week
+    }
+
+    /// Validates `xml` with `xmllint` against an approximation of the OpenMensa feed schema, see
+    /// the note in the schema file.
+    ///
+    /// `xmllint` comes with libxml2 (e.g. the package libxml2-utils) and not with Rust. Where it is
+    /// not installed, validation is skipped with a note and `None` is returned.
+    fn validate(xml: &str) -> Option<Result<(), String>> {
+        let mut xmllint = match Command::new("xmllint")
+            .args(["--noout", "--schema", FEED_SCHEMA, "-"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(xmllint) => xmllint,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                eprintln!("xmllint not found, skipping schema validation");
+                return None;
+            }
+            Err(e) => panic!("can not run xmllint: {e}"),
+        };
+        xmllint
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(xml.as_bytes())
+            .unwrap();
+
+        let output = xmllint.wait_with_output().unwrap();
+        if output.status.success() {
+            Some(Ok(()))
+        } else {
+            Some(Err(String::from_utf8_lossy(&output.stderr).into_owned()))
+        }
+    }
+
+    /// Renders a feed of the academica for a week with menus and a week with a closed day.
+    fn two_week_feed() -> String {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let currywurst = Dish::new(
+            "Currywurst & Pommes".to_owned(),
+            vec![],
+            vec![Label::Pork],
+            Some(Price::new(250, Some(390), Some(460))),
+        );
+        let next_week = WeekMenu::new(BTreeMap::from([
+            (date(21), DayMenu::Closed),
+            (
+                date(22),
+                DayMenu::Open(Menu::new::<MenuExtra>(
+                    BTreeMap::from([(Category::Classic, vec![currywurst])]),
+                    vec![],
+                )),
+            ),
+        ]));
+
+        render_feed(Canteen::Academica, &[academica_week(), next_week]).unwrap()
+    }
+
+    #[test]
+    fn it_renders_openmensa_feeds() {
+        let feed = two_week_feed();
+
+        assert!(feed.contains(r#"<day date="2024-10-14">"#));
+        assert!(feed.contains(r#"<day date="2024-10-21"><closed/></day>"#));
+        assert!(feed.contains(
+            "          <name>Currywurst &amp; Pommes</name>\n\
+            \x20         <note>Schwein</note>\n\
+            \x20         <price role=\"student\">2.50</price>\n\
+            \x20         <price role=\"employee\">3.90</price>\n\
+            \x20         <price role=\"other\">4.60</price>\n"
+        ));
+    }
+
+    #[test]
+    fn it_renders_feeds_valid_against_the_schema() {
+        let Some(valid) = validate(&two_week_feed()) else {
+            return;
+        };
+        valid.unwrap();
+
+        let invalid = two_week_feed().replace(r#"role="student""#, r#"role="teacher""#);
+        assert!(matches!(validate(&invalid), Some(Err(_))));
+    }
+
+    #[tokio::test]
+    async fn it_serves_feeds_by_canteen() {
+        let provider = InMemoryMenuProvider::new();
+        provider.insert(Canteen::Academica, MenuWeek::Current, academica_week());
+
+        let addr = crate::web::spawn_local(Arc::new(provider)).await;
+
+        let get = |path: &str| reqwest::get(format!("http://{addr}{path}"));
+
+        let resp = get("/openmensa/academica/feed.xml").await.unwrap();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers()["content-type"].to_str().unwrap(),
+            "application/xml"
+        );
+        assert!(resp
+            .text()
+            .await
+            .unwrap()
+            .contains(r#"<day date="2024-10-14">"#));
+
+        let resp = get("/openmensa/mcdonalds/feed.xml").await.unwrap();
+        assert_eq!(resp.status(), 404);
+
+        // No menu of the vita is in memory
+        let resp = get("/openmensa/vita/feed.xml").await.unwrap();
+        assert_eq!(resp.status(), 502);
+    }
+}